@@ -382,6 +382,12 @@ extern "C" {
 
     pub fn rocksdb_iter_seek(iterator: *mut rocksdb_iterator_t, k: *const c_char, klen: size_t);
 
+    pub fn rocksdb_iter_seek_for_prev(
+        iterator: *mut rocksdb_iterator_t,
+        k: *const c_char,
+        klen: size_t,
+    );
+
     pub fn rocksdb_iter_next(iterator: *mut rocksdb_iterator_t);
 
     pub fn rocksdb_iter_prev(iterator: *mut rocksdb_iterator_t);
@@ -830,6 +836,13 @@ extern "C" {
 
     pub fn rocksdb_options_set_max_manifest_file_size(opt: *mut rocksdb_options_t, v: size_t);
 
+    pub fn rocksdb_options_set_writable_file_max_buffer_size(opt: *mut rocksdb_options_t, v: u64);
+
+    pub fn rocksdb_options_set_skip_stats_update_on_db_open(
+        opt: *mut rocksdb_options_t,
+        val: c_uchar,
+    );
+
     pub fn rocksdb_options_set_table_cache_numshardbits(opt: *mut rocksdb_options_t, v: c_int);
 
     pub fn rocksdb_options_set_table_cache_remove_scan_count_limit(
@@ -1137,6 +1150,8 @@ extern "C" {
 
     pub fn rocksdb_readoptions_set_tailing(opt: *mut rocksdb_readoptions_t, v: c_uchar);
 
+    pub fn rocksdb_readoptions_set_total_order_seek(opt: *mut rocksdb_readoptions_t, v: c_uchar);
+
     pub fn rocksdb_readoptions_set_readahead_size(opt: *mut rocksdb_readoptions_t, v: size_t);
 
     // Write options
@@ -1175,6 +1190,14 @@ extern "C" {
 
     pub fn rocksdb_env_set_high_priority_background_threads(env: *mut rocksdb_env_t, n: c_int);
 
+    pub fn rocksdb_env_lower_thread_pool_io_priority(env: *mut rocksdb_env_t);
+
+    pub fn rocksdb_env_lower_high_priority_thread_pool_io_priority(env: *mut rocksdb_env_t);
+
+    pub fn rocksdb_env_lower_thread_pool_cpu_priority(env: *mut rocksdb_env_t);
+
+    pub fn rocksdb_env_lower_high_priority_thread_pool_cpu_priority(env: *mut rocksdb_env_t);
+
     pub fn rocksdb_env_join_all_threads(env: *mut rocksdb_env_t);
 
     pub fn rocksdb_env_destroy(env: *mut rocksdb_env_t);
@@ -1309,8 +1332,110 @@ extern "C" {
         errptr: *mut *mut c_char,
     );
 
+    pub fn rocksdb_get_latest_sequence_number(db: *mut rocksdb_t) -> u64;
+
+    pub fn rocksdb_get_updates_since(
+        db: *mut rocksdb_t,
+        seq_number: u64,
+        options: *const rocksdb_wal_readoptions_t,
+        errptr: *mut *mut c_char,
+    ) -> *mut rocksdb_wal_iterator_t;
+
+    pub fn rocksdb_wal_iter_next(iter: *mut rocksdb_wal_iterator_t);
+
+    pub fn rocksdb_wal_iter_valid(iter: *const rocksdb_wal_iterator_t) -> c_uchar;
+
+    pub fn rocksdb_wal_iter_status(iter: *const rocksdb_wal_iterator_t, errptr: *mut *mut c_char);
+
+    pub fn rocksdb_wal_iter_get_batch(
+        iter: *const rocksdb_wal_iterator_t,
+        seq: *mut u64,
+    ) -> *mut rocksdb_writebatch_t;
+
+    pub fn rocksdb_wal_iter_destroy(iter: *const rocksdb_wal_iterator_t);
+
+    pub fn rocksdb_set_options(
+        db: *mut rocksdb_t,
+        count: c_int,
+        keys: *const *const c_char,
+        values: *const *const c_char,
+        errptr: *mut *mut c_char,
+    );
+
+    pub fn rocksdb_set_options_cf(
+        db: *mut rocksdb_t,
+        handle: *mut rocksdb_column_family_handle_t,
+        count: c_int,
+        keys: *const *const c_char,
+        values: *const *const c_char,
+        errptr: *mut *mut c_char,
+    );
+
     pub fn rocksdb_free(ptr: *mut c_void);
 
+    // Env options
+
+    pub fn rocksdb_envoptions_create() -> *mut rocksdb_envoptions_t;
+
+    pub fn rocksdb_envoptions_destroy(opt: *mut rocksdb_envoptions_t);
+
+    // SST file writer
+
+    pub fn rocksdb_sstfilewriter_create(
+        env: *const rocksdb_envoptions_t,
+        io_options: *const rocksdb_options_t,
+    ) -> *mut rocksdb_sstfilewriter_t;
+
+    pub fn rocksdb_sstfilewriter_open(
+        writer: *mut rocksdb_sstfilewriter_t,
+        name: *const c_char,
+        errptr: *mut *mut c_char,
+    );
+
+    pub fn rocksdb_sstfilewriter_put(
+        writer: *mut rocksdb_sstfilewriter_t,
+        key: *const c_char,
+        keylen: size_t,
+        val: *const c_char,
+        vallen: size_t,
+        errptr: *mut *mut c_char,
+    );
+
+    pub fn rocksdb_sstfilewriter_finish(
+        writer: *mut rocksdb_sstfilewriter_t,
+        errptr: *mut *mut c_char,
+    );
+
+    pub fn rocksdb_sstfilewriter_destroy(writer: *mut rocksdb_sstfilewriter_t);
+
+    // External file ingestion
+
+    pub fn rocksdb_ingestexternalfileoptions_create() -> *mut rocksdb_ingestexternalfileoptions_t;
+
+    pub fn rocksdb_ingestexternalfileoptions_set_move_files(
+        opt: *mut rocksdb_ingestexternalfileoptions_t,
+        move_files: c_uchar,
+    );
+
+    pub fn rocksdb_ingestexternalfileoptions_destroy(opt: *mut rocksdb_ingestexternalfileoptions_t);
+
+    pub fn rocksdb_ingest_external_file(
+        db: *mut rocksdb_t,
+        file_list: *const *const c_char,
+        list_len: size_t,
+        opt: *const rocksdb_ingestexternalfileoptions_t,
+        errptr: *mut *mut c_char,
+    );
+
+    pub fn rocksdb_ingest_external_file_cf(
+        db: *mut rocksdb_t,
+        handle: *mut rocksdb_column_family_handle_t,
+        file_list: *const *const c_char,
+        list_len: size_t,
+        opt: *const rocksdb_ingestexternalfileoptions_t,
+        errptr: *mut *mut c_char,
+    );
+
     // Transactions DB
 
     pub fn rocksdb_transactiondb_open(
@@ -1566,3 +1691,13 @@ pub enum rocksdb_transaction_options_t {}
 pub enum rocksdb_transaction_t {}
 
 pub enum rocksdb_checkpoint_t {}
+
+pub enum rocksdb_envoptions_t {}
+
+pub enum rocksdb_sstfilewriter_t {}
+
+pub enum rocksdb_ingestexternalfileoptions_t {}
+
+pub enum rocksdb_wal_iterator_t {}
+
+pub enum rocksdb_wal_readoptions_t {}
@@ -0,0 +1,386 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bulk loading of sorted data through external SST files.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate tempdir;
+//! extern crate exonum_rocksdb;
+//!
+//! use exonum_rocksdb::bulk_load::BulkLoader;
+//! use exonum_rocksdb::{Options, DB};
+//! use tempdir::TempDir;
+//!
+//! # fn main() {
+//! let temp_dir = TempDir::new("storage_bulk_load").unwrap();
+//! let mut opts = Options::default();
+//! opts.create_if_missing(true);
+//! let db = DB::open(&opts, temp_dir.path()).unwrap();
+//!
+//! let entries = (0..1000u32).map(|i| (format!("key{:04}", i), format!("value{}", i)));
+//! BulkLoader::new(&db, &opts).load(entries).unwrap();
+//! assert_eq!(&*db.get(b"key0042").unwrap().unwrap(), b"value42");
+//! # }
+//! ```
+
+use ffi;
+use utils::{get_cf_option, to_cpath};
+use {ColumnFamily, Error, Options, DB};
+
+use libc::{c_char, c_uchar, size_t};
+use tempdir::TempDir;
+
+use std::collections::VecDeque;
+use std::ffi::CString;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::thread::{self, JoinHandle};
+
+const DEFAULT_ENTRIES_PER_FILE: usize = 100_000;
+const DEFAULT_MAX_THREADS: usize = 4;
+
+/// Loads a sorted stream of key-value pairs into a database.
+///
+/// The stream is split into chunks, each chunk is written into a separate SST file
+/// on a background thread, and the resulting files are then ingested into
+/// the database at once. This is considerably faster than writing the same data
+/// through `put` or `write`, as it bypasses the WAL and the memtables, and
+/// the ingested files don't need to be compacted.
+///
+/// Keys must be supplied in strictly ascending order according to the comparator
+/// of the column family; otherwise the load fails and the database is left unchanged.
+/// The order is checked both within each SST file and across the boundaries
+/// of consecutive files.
+pub struct BulkLoader<'a> {
+    db: &'a DB,
+    opts: &'a Options,
+    cf: Option<ColumnFamily>,
+    entries_per_file: usize,
+    max_threads: usize,
+}
+
+impl<'a> BulkLoader<'a> {
+    /// Creates a loader into the default column family.
+    ///
+    /// `opts` must be compatible with the options the column family has been opened
+    /// with (in particular, use the same comparator), since the SST files are built
+    /// with them.
+    pub fn new(db: &'a DB, opts: &'a Options) -> BulkLoader<'a> {
+        BulkLoader {
+            db,
+            opts,
+            cf: None,
+            entries_per_file: DEFAULT_ENTRIES_PER_FILE,
+            max_threads: DEFAULT_MAX_THREADS,
+        }
+    }
+
+    /// Creates a loader into the given column family.
+    pub fn new_cf(db: &'a DB, cf: ColumnFamily, opts: &'a Options) -> BulkLoader<'a> {
+        BulkLoader {
+            cf: Some(cf),
+            ..BulkLoader::new(db, opts)
+        }
+    }
+
+    /// Sets the number of entries written into a single SST file. Up to
+    /// `max_threads` chunks of this size are buffered in memory at a time.
+    ///
+    /// Default: `100000`
+    pub fn set_entries_per_file(&mut self, entries_per_file: usize) {
+        assert!(entries_per_file > 0, "entries_per_file must be positive");
+        self.entries_per_file = entries_per_file;
+    }
+
+    /// Sets the maximum number of SST files written concurrently.
+    ///
+    /// Default: `4`
+    pub fn set_max_threads(&mut self, max_threads: usize) {
+        assert!(max_threads > 0, "max_threads must be positive");
+        self.max_threads = max_threads;
+    }
+
+    /// Writes `entries` into SST files and ingests them into the database.
+    ///
+    /// Automatic compactions of the target column family are disabled during
+    /// the ingestion and restored to their previous setting afterwards. SST files
+    /// of concurrent loads into the same database are written in parallel, but
+    /// the loads are ingested one at a time.
+    pub fn load<I, K, V>(&self, entries: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        // Keeping the files next to the database lets the ingestion hard-link them
+        // instead of copying.
+        let temp_dir = TempDir::new_in(self.db.path(), "bulk_load").map_err(|e| {
+            Error::new(format!(
+                "Failed to create temporary directory for bulk load: `{:?}`.",
+                e
+            ))
+        })?;
+
+        let mut files = Vec::new();
+        {
+            let mut workers = Workers::default();
+            let mut chunk = Vec::with_capacity(self.entries_per_file);
+            // Last key of the previous chunk, which the next chunk has to start after.
+            let mut last_key: Option<Vec<u8>> = None;
+            for (key, value) in entries {
+                chunk.push((key.as_ref().to_vec(), value.as_ref().to_vec()));
+                if chunk.len() == self.entries_per_file {
+                    let full_chunk =
+                        mem::replace(&mut chunk, Vec::with_capacity(self.entries_per_file));
+                    self.check_chunk_order(temp_dir.path(), &mut last_key, &full_chunk)?;
+                    self.write_file(&mut workers, temp_dir.path(), &mut files, full_chunk)?;
+                }
+            }
+            if !chunk.is_empty() {
+                self.check_chunk_order(temp_dir.path(), &mut last_key, &chunk)?;
+                self.write_file(&mut workers, temp_dir.path(), &mut files, chunk)?;
+            }
+            workers.join_all()?;
+        }
+
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        // Otherwise an overlapping load could save the setting disabled by this one
+        // and restore it after this load has finished.
+        let _lock = self.db.bulk_load_lock.lock().unwrap();
+        let disable_auto_compactions = self.auto_compactions_disabled()?;
+        self.set_auto_compactions_disabled(true)?;
+        let result = self.ingest(&files);
+        let restored = self.set_auto_compactions_disabled(disable_auto_compactions);
+        result.and(restored)
+    }
+
+    /// Checks that the first key of `chunk` follows `last_key`, and updates `last_key`
+    /// to the last key of `chunk`. The keys within the chunk are checked by the SST
+    /// file writer itself.
+    fn check_chunk_order(
+        &self,
+        dir: &Path,
+        last_key: &mut Option<Vec<u8>>,
+        chunk: &[(Vec<u8>, Vec<u8>)],
+    ) -> Result<(), Error> {
+        if let Some(ref last_key) = *last_key {
+            // Let a writer with the same options decide, so that the comparator
+            // of the column family is taken into account.
+            let first_key = &chunk[0].0;
+            let probe = [
+                (last_key.clone(), Vec::new()),
+                (first_key.clone(), Vec::new()),
+            ];
+            SstFileWriter::new(self.opts).add_all(&dir.join("order_check.sst"), &probe)?;
+        }
+        *last_key = chunk.last().map(|entry| entry.0.clone());
+        Ok(())
+    }
+
+    fn write_file(
+        &self,
+        workers: &mut Workers,
+        dir: &Path,
+        files: &mut Vec<PathBuf>,
+        chunk: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> Result<(), Error> {
+        while workers.len() >= self.max_threads {
+            workers.join_next()?;
+        }
+
+        let path = dir.join(format!("{:06}.sst", files.len()));
+        files.push(path.clone());
+        let writer = SstFileWriter::new(self.opts);
+        workers.spawn(move || writer.write(&path, &chunk));
+        Ok(())
+    }
+
+    fn ingest(&self, files: &[PathBuf]) -> Result<(), Error> {
+        let c_files = files.iter().map(to_cpath).collect::<Result<Vec<_>, _>>()?;
+        let file_ptrs: Vec<*const c_char> = c_files.iter().map(|file| file.as_ptr()).collect();
+
+        unsafe {
+            let opts = ffi::rocksdb_ingestexternalfileoptions_create();
+            ffi::rocksdb_ingestexternalfileoptions_set_move_files(opts, true as c_uchar);
+            let mut err: *mut c_char = ::std::ptr::null_mut();
+            match self.cf {
+                Some(cf) => ffi::rocksdb_ingest_external_file_cf(
+                    self.db.inner,
+                    cf.inner,
+                    file_ptrs.as_ptr(),
+                    file_ptrs.len() as size_t,
+                    opts,
+                    &mut err,
+                ),
+                None => ffi::rocksdb_ingest_external_file(
+                    self.db.inner,
+                    file_ptrs.as_ptr(),
+                    file_ptrs.len() as size_t,
+                    opts,
+                    &mut err,
+                ),
+            }
+            ffi::rocksdb_ingestexternalfileoptions_destroy(opts);
+            if !err.is_null() {
+                return Err(Error::new(::ffi_util::error_message(err)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the current `disable_auto_compactions` setting of the column family
+    /// from the latest `OPTIONS` file, which RocksDB rewrites on every change.
+    fn auto_compactions_disabled(&self) -> Result<bool, Error> {
+        let cf_name = match self.cf {
+            Some(cf) => {
+                let cfs = self.db.cfs.read().unwrap();
+                match cfs.iter().find(|&(_, handle)| handle.inner == cf.inner) {
+                    Some((name, _)) => name.clone(),
+                    None => return Err(Error::new("Invalid column family.".to_owned())),
+                }
+            }
+            None => "default".to_owned(),
+        };
+        let value = get_cf_option(self.db.path(), &cf_name, "disable_auto_compactions")?;
+        Ok(value.map_or(false, |value| value == "true"))
+    }
+
+    fn set_auto_compactions_disabled(&self, disabled: bool) -> Result<(), Error> {
+        let key = CString::new("disable_auto_compactions").unwrap();
+        let value = CString::new(if disabled { "true" } else { "false" }).unwrap();
+        let keys = [key.as_ptr()];
+        let values = [value.as_ptr()];
+
+        unsafe {
+            match self.cf {
+                Some(cf) => ffi_try!(ffi::rocksdb_set_options_cf(
+                    self.db.inner,
+                    cf.inner,
+                    1,
+                    keys.as_ptr(),
+                    values.as_ptr()
+                )),
+                None => ffi_try!(ffi::rocksdb_set_options(
+                    self.db.inner,
+                    1,
+                    keys.as_ptr(),
+                    values.as_ptr()
+                )),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Background threads writing SST files. Threads still running when this is dropped
+/// are joined, so that no file is written after the load has returned.
+#[derive(Default)]
+struct Workers {
+    handles: VecDeque<JoinHandle<Result<(), Error>>>,
+}
+
+impl Workers {
+    fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    fn spawn<F>(&mut self, f: F)
+    where
+        F: FnOnce() -> Result<(), Error> + Send + 'static,
+    {
+        self.handles.push_back(thread::spawn(f));
+    }
+
+    fn join_next(&mut self) -> Result<(), Error> {
+        match self.handles.pop_front() {
+            Some(handle) => match handle.join() {
+                Ok(result) => result,
+                Err(_) => Err(Error::new("SST file writer thread panicked.".to_owned())),
+            },
+            None => Ok(()),
+        }
+    }
+
+    fn join_all(&mut self) -> Result<(), Error> {
+        while !self.handles.is_empty() {
+            self.join_next()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Workers {
+    fn drop(&mut self) {
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+struct SstFileWriter {
+    inner: *mut ffi::rocksdb_sstfilewriter_t,
+}
+
+// The writer is created on the loading thread and then used exclusively by one worker.
+unsafe impl Send for SstFileWriter {}
+
+impl SstFileWriter {
+    fn new(opts: &Options) -> SstFileWriter {
+        unsafe {
+            let env_opts = ffi::rocksdb_envoptions_create();
+            let inner = ffi::rocksdb_sstfilewriter_create(env_opts, opts.inner);
+            ffi::rocksdb_envoptions_destroy(env_opts);
+            SstFileWriter { inner }
+        }
+    }
+
+    fn write(self, path: &Path, entries: &[(Vec<u8>, Vec<u8>)]) -> Result<(), Error> {
+        self.add_all(path, entries)?;
+        unsafe {
+            ffi_try!(ffi::rocksdb_sstfilewriter_finish(self.inner));
+        }
+        Ok(())
+    }
+
+    /// Opens the file at `path` and adds `entries` to it without finishing the file.
+    fn add_all(&self, path: &Path, entries: &[(Vec<u8>, Vec<u8>)]) -> Result<(), Error> {
+        let cpath = to_cpath(path)?;
+        unsafe {
+            ffi_try!(ffi::rocksdb_sstfilewriter_open(self.inner, cpath.as_ptr()));
+            for &(ref key, ref value) in entries {
+                ffi_try!(ffi::rocksdb_sstfilewriter_put(
+                    self.inner,
+                    key.as_ptr() as *const c_char,
+                    key.len() as size_t,
+                    value.as_ptr() as *const c_char,
+                    value.len() as size_t
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SstFileWriter {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_sstfilewriter_destroy(self.inner);
+        }
+    }
+}
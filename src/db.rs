@@ -15,20 +15,27 @@
 
 use ffi;
 use ffi_util::opt_bytes_to_ptr;
-use {ColumnFamily, Error, Options, WriteOptions, DB};
+use instrument::{instrument, IteratorOperations};
+use watch::PrefixWatcher;
+use {ColumnFamily, ColumnFamilyDescriptor, Error, Options, WriteOptions, DB};
 
 use libc::{c_char, c_int, c_uchar, c_void, size_t};
 
+use std::collections::hash_map::DefaultHasher;
 use std::collections::BTreeMap;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::fmt;
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::ops::Deref;
 use std::path::Path;
 use std::ptr;
 use std::slice;
 use std::str;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Number of locks `DB::update` calls are distributed over.
+const UPDATE_LOCK_STRIPES: usize = 64;
 
 pub fn new_bloom_filter(bits: c_int) -> *mut ffi::rocksdb_filterpolicy_t {
     unsafe { ffi::rocksdb_filterpolicy_create_bloom(bits) }
@@ -67,6 +74,19 @@ pub enum DBRecoveryMode {
     SkipAnyCorruptedRecord = ffi::rocksdb_recovery_mode_skip_any_corrupted_record as isize,
 }
 
+#[derive(Clone, Copy)]
+enum AccessType {
+    ReadWrite,
+    ReadOnly { error_if_log_file_exist: bool },
+}
+
+/// Describes column families `cfs` opened with default options.
+fn default_descriptors(cfs: &[&str]) -> Vec<ColumnFamilyDescriptor> {
+    cfs.iter()
+        .map(|name| ColumnFamilyDescriptor::new(*name, Options::default()))
+        .collect()
+}
+
 /// An atomic batch of write operations.
 ///
 /// Making an atomic commit of several writes:
@@ -93,8 +113,17 @@ pub struct WriteBatch {
     inner: *mut ffi::rocksdb_writebatch_t,
 }
 
+/// Options for read operations.
+///
+/// Cloning the options copies the values set through the methods of this type,
+/// which are also what the `Debug` output shows.
 pub struct ReadOptions {
     pub inner: *mut ffi::rocksdb_readoptions_t,
+    fill_cache: bool,
+    snapshot: Option<*const ffi::rocksdb_snapshot_t>,
+    // RocksDB only keeps a pointer to the bound, so it must live as long as the options.
+    iterate_upper_bound: Option<Vec<u8>>,
+    total_order_seek: bool,
 }
 
 /// A consistent view of the database at the point of creation.
@@ -163,6 +192,9 @@ pub struct Snapshot<'a> {
 /// ```
 pub struct DBRawIterator {
     inner: *mut ffi::rocksdb_iterator_t,
+    // Kept alive for the iterator bounds referenced by `inner`.
+    _readopts: ReadOptions,
+    operations: IteratorOperations,
 }
 
 /// An iterator over a database or column family, with specifiable
@@ -218,11 +250,40 @@ pub enum IteratorMode<'a> {
     From(&'a [u8], Direction),
 }
 
+/// Disk space used by a single column family.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ColumnFamilyDiskUsage {
+    /// Total size of the live SST files.
+    pub sst_bytes: u64,
+    /// Total size of the live blob files. Always zero with the bundled RocksDB,
+    /// which does not report blob file sizes.
+    pub blob_bytes: u64,
+}
+
+/// Disk space used by a database, as reported by `DB::disk_usage`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiskUsage {
+    /// Total size of the live SST files of all column families.
+    pub sst_bytes: u64,
+    /// Total size of the live blob files of all column families. Always zero with
+    /// the bundled RocksDB, which does not report blob file sizes.
+    pub blob_bytes: u64,
+    /// Total size of the write-ahead log files in the database directory.
+    pub wal_bytes: u64,
+    /// Total size of the write-ahead log files kept in the `archive` subdirectory
+    /// because of `Options::set_wal_ttl_seconds` or `Options::set_wal_size_limit_mb`.
+    pub archived_wal_bytes: u64,
+    /// Disk usage of every column family, keyed by its name.
+    pub column_families: BTreeMap<String, ColumnFamilyDiskUsage>,
+}
+
 impl DBRawIterator {
-    fn new(db: &DB, readopts: &ReadOptions) -> DBRawIterator {
+    fn new(db: &DB, readopts: ReadOptions) -> DBRawIterator {
         unsafe {
             DBRawIterator {
                 inner: ffi::rocksdb_create_iterator(db.inner, readopts.inner),
+                _readopts: readopts,
+                operations: IteratorOperations::new(db, None),
             }
         }
     }
@@ -230,11 +291,13 @@ impl DBRawIterator {
     fn new_cf(
         db: &DB,
         cf_handle: ColumnFamily,
-        readopts: &ReadOptions,
+        readopts: ReadOptions,
     ) -> Result<DBRawIterator, Error> {
         unsafe {
             Ok(DBRawIterator {
                 inner: ffi::rocksdb_create_iterator_cf(db.inner, readopts.inner, cf_handle.inner),
+                _readopts: readopts,
+                operations: IteratorOperations::new(db, Some(cf_handle)),
             })
         }
     }
@@ -282,9 +345,9 @@ impl DBRawIterator {
     /// # }
     /// ```
     pub fn seek_to_first(&mut self) {
-        unsafe {
+        self.operations.seek(|| unsafe {
             ffi::rocksdb_iter_seek_to_first(self.inner);
-        }
+        });
     }
 
     /// Seeks to the last key in the database.
@@ -325,9 +388,9 @@ impl DBRawIterator {
     /// # }
     /// ```
     pub fn seek_to_last(&mut self) {
-        unsafe {
+        self.operations.seek(|| unsafe {
             ffi::rocksdb_iter_seek_to_last(self.inner);
-        }
+        });
     }
 
     /// Seeks to the specified key or the first key that lexicographically follows it.
@@ -361,52 +424,55 @@ impl DBRawIterator {
     /// # }
     /// ```
     pub fn seek(&mut self, key: &[u8]) {
-        unsafe {
+        self.operations.seek(|| unsafe {
             ffi::rocksdb_iter_seek(
                 self.inner,
                 key.as_ptr() as *const c_char,
                 key.len() as size_t,
             );
-        }
+        });
     }
 
-    /*
-        SeekForPrev was added in RocksDB 4.13 but not implemented in the C API until RocksDB 5.0
-
-        /// Seeks to the specified key, or the first key that lexicographically precedes it.
-        ///
-        /// Like ``.seek()`` this method will attempt to seek to the specified key.
-        /// The difference with ``.seek()`` is that if the specified key do not exist, this method will
-        /// seek to key that lexicographically precedes it instead.
-        ///
-        /// # Examples
-        ///
-        /// ```rust
-        /// extern crate exonum_rocksdb;
-        /// extern crate tempdir;
-        ///
-        /// use exonum_rocksdb::DB;
-        /// use tempdir::TempDir;
-        ///
-        /// # fn main() {
-        /// let temp_dir = TempDir::new("storage8").unwrap();
-        /// let mut db = DB::open_default(temp_dir.path()).unwrap();
-        /// let mut iter = db.raw_iterator();
-        ///
-        /// // Read the last key that starts with 'a'
-        ///
-        /// iter.seek_for_prev(b"b");
-        ///
-        /// if iter.valid() {
-        ///    println!("{:?} {:?}", iter.key(), iter.value());
-        /// } else {
-        ///    // There are no keys in the database
-        /// }
-        pub fn seek_for_prev(&mut self, key: &[u8]) {
-            unsafe { ffi::rocksdb_iter_seek_for_prev(
-                        self.inner, key.as_ptr() as *const c_char, key.len() as size_t); }
-        }
-    */
+    /// Seeks to the specified key, or the first key that lexicographically precedes it.
+    ///
+    /// Like ``.seek()`` this method will attempt to seek to the specified key.
+    /// The difference with ``.seek()`` is that if the specified key do not exist, this method will
+    /// seek to key that lexicographically precedes it instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// extern crate exonum_rocksdb;
+    /// extern crate tempdir;
+    ///
+    /// use exonum_rocksdb::DB;
+    /// use tempdir::TempDir;
+    ///
+    /// # fn main() {
+    /// let temp_dir = TempDir::new("storage8").unwrap();
+    /// let mut db = DB::open_default(temp_dir.path()).unwrap();
+    /// let mut iter = db.raw_iterator();
+    ///
+    /// // Read the last key that starts with 'a'
+    ///
+    /// iter.seek_for_prev(b"b");
+    ///
+    /// if iter.valid() {
+    ///    println!("{:?} {:?}", iter.key(), iter.value());
+    /// } else {
+    ///    // There are no keys in the database
+    /// }
+    /// # }
+    /// ```
+    pub fn seek_for_prev(&mut self, key: &[u8]) {
+        self.operations.seek(|| unsafe {
+            ffi::rocksdb_iter_seek_for_prev(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            );
+        });
+    }
 
     /// Seeks to the next key.
     ///
@@ -473,6 +539,17 @@ impl DBRawIterator {
     pub fn value(&self) -> Option<Vec<u8>> {
         unsafe { self.value_inner().map(|value| value.to_vec()) }
     }
+
+    /// Returns the error encountered by the iterator, if any.
+    ///
+    /// An iterator becomes invalid both when it runs past the end of the data and when
+    /// an I/O error or a corruption occurs; this method tells the two cases apart.
+    pub fn status(&self) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_iter_get_error(self.inner));
+        }
+        Ok(())
+    }
 }
 
 impl Drop for DBRawIterator {
@@ -484,7 +561,7 @@ impl Drop for DBRawIterator {
 }
 
 impl DBIterator {
-    fn new(db: &DB, readopts: &ReadOptions, mode: IteratorMode) -> DBIterator {
+    fn new(db: &DB, readopts: ReadOptions, mode: IteratorMode) -> DBIterator {
         let mut rv = DBIterator {
             raw: DBRawIterator::new(db, readopts),
             direction: Direction::Forward, // blown away by set_mode()
@@ -497,7 +574,7 @@ impl DBIterator {
     fn new_cf(
         db: &DB,
         cf_handle: ColumnFamily,
-        readopts: &ReadOptions,
+        readopts: ReadOptions,
         mode: IteratorMode,
     ) -> Result<DBIterator, Error> {
         let mut rv = DBIterator {
@@ -519,16 +596,98 @@ impl DBIterator {
                 self.raw.seek_to_last();
                 self.direction = Direction::Reverse;
             }
-            IteratorMode::From(key, dir) => {
-                // TODO: Should use seek_for_prev when reversing
+            IteratorMode::From(key, Direction::Forward) => {
                 self.raw.seek(key);
-                self.direction = dir;
+                self.direction = Direction::Forward;
+            }
+            IteratorMode::From(key, Direction::Reverse) => {
+                self.raw.seek_for_prev(key);
+                self.direction = Direction::Reverse;
             }
         };
 
         self.just_seeked = true;
     }
 
+    /// Seeks to the first key, after which the iterator goes forward.
+    pub fn seek_to_first(&mut self) {
+        self.set_mode(IteratorMode::Start);
+    }
+
+    /// Seeks to the last key, after which the iterator goes backward.
+    pub fn seek_to_last(&mut self) {
+        self.set_mode(IteratorMode::End);
+    }
+
+    /// Seeks to the specified key, or the first key that lexicographically follows it
+    /// if the iterator goes forward, or precedes it if the iterator goes backward.
+    /// The direction of the iterator is kept; the next item returned is the one
+    /// the iterator has been positioned at.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// extern crate exonum_rocksdb;
+    /// extern crate tempdir;
+    ///
+    /// use exonum_rocksdb::{DB, IteratorMode};
+    /// use tempdir::TempDir;
+    ///
+    /// # fn main() {
+    /// let temp_dir = TempDir::new("storage_seek").unwrap();
+    /// let db = DB::open_default(temp_dir.path()).unwrap();
+    /// for key in &[b"a1", b"a2", b"b1", b"b2"] {
+    ///     db.put(*key, b"").unwrap();
+    /// }
+    ///
+    /// // Scan two ranges with the same iterator.
+    /// let mut iter = db.iterator(IteratorMode::Start);
+    /// assert_eq!(&*iter.next().unwrap().0, b"a1");
+    /// iter.seek(b"b");
+    /// assert_eq!(&*iter.next().unwrap().0, b"b1");
+    /// # }
+    /// ```
+    pub fn seek(&mut self, key: &[u8]) {
+        match self.direction {
+            Direction::Forward => self.raw.seek(key),
+            Direction::Reverse => self.raw.seek_for_prev(key),
+        }
+        self.just_seeked = true;
+    }
+
+    /// Seeks to the specified key, or the first key that lexicographically precedes it.
+    /// The direction of the iterator is kept; the next item returned is the one
+    /// the iterator has been positioned at.
+    pub fn seek_for_prev(&mut self, key: &[u8]) {
+        self.raw.seek_for_prev(key);
+        self.just_seeked = true;
+    }
+
+    /// Changes the direction of the iterator without moving it. The next item returned
+    /// is the neighbor of the last returned item in the new direction, or the item
+    /// the iterator has been positioned at if no item has been returned since the last seek.
+    ///
+    /// If the iterator has run past the end in the previous direction, it is positioned
+    /// at the last key when switching to `Reverse`, or at the first key when switching
+    /// to `Forward`.
+    pub fn set_direction(&mut self, direction: Direction) {
+        let reversed = match (&self.direction, &direction) {
+            (&Direction::Forward, &Direction::Reverse)
+            | (&Direction::Reverse, &Direction::Forward) => true,
+            _ => false,
+        };
+        // An exhausted iterator has no position to turn around at, so start over
+        // from the end it has run past.
+        if reversed && !self.raw.valid() {
+            match direction {
+                Direction::Forward => self.raw.seek_to_first(),
+                Direction::Reverse => self.raw.seek_to_last(),
+            }
+            self.just_seeked = true;
+        }
+        self.direction = direction;
+    }
+
     pub fn valid(&self) -> bool {
         self.raw.valid()
     }
@@ -539,8 +698,9 @@ impl Iterator for DBIterator {
 
     fn next(&mut self) -> Option<KVBytes> {
         // Initial call to next() after seeking should not move the iterator
-        // or the first item will not be returned
-        if !self.just_seeked {
+        // or the first item will not be returned. An exhausted iterator
+        // must not be moved either.
+        if !self.just_seeked && self.raw.valid() {
             match self.direction {
                 Direction::Forward => self.raw.next(),
                 Direction::Reverse => self.raw.prev(),
@@ -579,7 +739,7 @@ impl<'a> Snapshot<'a> {
     pub fn iterator(&self, mode: IteratorMode) -> DBIterator {
         let mut readopts = ReadOptions::default();
         readopts.set_snapshot(self);
-        DBIterator::new(self.db, &readopts, mode)
+        DBIterator::new(self.db, readopts, mode)
     }
 
     pub fn iterator_cf(
@@ -589,19 +749,19 @@ impl<'a> Snapshot<'a> {
     ) -> Result<DBIterator, Error> {
         let mut readopts = ReadOptions::default();
         readopts.set_snapshot(self);
-        DBIterator::new_cf(self.db, cf_handle, &readopts, mode)
+        DBIterator::new_cf(self.db, cf_handle, readopts, mode)
     }
 
     pub fn raw_iterator(&self) -> DBRawIterator {
         let mut readopts = ReadOptions::default();
         readopts.set_snapshot(self);
-        DBRawIterator::new(self.db, &readopts)
+        DBRawIterator::new(self.db, readopts)
     }
 
     pub fn raw_iterator_cf(&self, cf_handle: ColumnFamily) -> Result<DBRawIterator, Error> {
         let mut readopts = ReadOptions::default();
         readopts.set_snapshot(self);
-        DBRawIterator::new_cf(self.db, cf_handle, &readopts)
+        DBRawIterator::new_cf(self.db, cf_handle, readopts)
     }
 
     pub fn get(&self, key: &[u8]) -> Result<Option<DBVector>, Error> {
@@ -631,6 +791,16 @@ impl<'a> Inner for Snapshot<'a> {
     }
 }
 
+impl ColumnFamilyDescriptor {
+    /// Creates a descriptor of the column family `name` opened with `options`.
+    pub fn new<S: Into<String>>(name: S, options: Options) -> ColumnFamilyDescriptor {
+        ColumnFamilyDescriptor {
+            name: name.into(),
+            options,
+        }
+    }
+}
+
 impl DB {
     /// Open a database with default options.
     pub fn open_default<P: AsRef<Path>>(path: P) -> Result<DB, Error> {
@@ -652,7 +822,81 @@ impl DB {
     ///
     /// * Panics if the column family doesn't exist.
     pub fn open_cf<P: AsRef<Path>>(opts: &Options, path: P, cfs: &[&str]) -> Result<DB, Error> {
-        let path = path.as_ref();
+        DB::open_cf_descriptors(opts, path, default_descriptors(cfs))
+    }
+
+    /// Open a database with specified options and column families, each opened
+    /// with its own options.
+    ///
+    /// The default column family is opened with default options unless it is
+    /// listed in `cfs`.
+    pub fn open_cf_descriptors<P: AsRef<Path>>(
+        opts: &Options,
+        path: P,
+        cfs: Vec<ColumnFamilyDescriptor>,
+    ) -> Result<DB, Error> {
+        DB::open_cf_inner(opts, path.as_ref(), cfs, AccessType::ReadWrite)
+    }
+
+    /// Open an existing database for reading only.
+    ///
+    /// A read-only database does not take the lock on its directory, so it can be opened
+    /// while another process has the database open for writing; it will not see writes
+    /// made after it has been opened, though. All write operations fail.
+    ///
+    /// If `error_if_log_file_exist` is `true`, opening fails if the database has
+    /// a non-empty write-ahead log, i.e., was not closed cleanly or is in use.
+    pub fn open_for_read_only<P: AsRef<Path>>(
+        opts: &Options,
+        path: P,
+        error_if_log_file_exist: bool,
+    ) -> Result<DB, Error> {
+        DB::open_cf_for_read_only(opts, path, &[], error_if_log_file_exist)
+    }
+
+    /// Open an existing database with the specified column families for reading only.
+    ///
+    /// See `open_for_read_only` for details.
+    pub fn open_cf_for_read_only<P: AsRef<Path>>(
+        opts: &Options,
+        path: P,
+        cfs: &[&str],
+        error_if_log_file_exist: bool,
+    ) -> Result<DB, Error> {
+        DB::open_cf_descriptors_for_read_only(
+            opts,
+            path,
+            default_descriptors(cfs),
+            error_if_log_file_exist,
+        )
+    }
+
+    /// Open an existing database with the specified column families, each opened
+    /// with its own options, for reading only.
+    ///
+    /// See `open_for_read_only` for details.
+    pub fn open_cf_descriptors_for_read_only<P: AsRef<Path>>(
+        opts: &Options,
+        path: P,
+        cfs: Vec<ColumnFamilyDescriptor>,
+        error_if_log_file_exist: bool,
+    ) -> Result<DB, Error> {
+        DB::open_cf_inner(
+            opts,
+            path.as_ref(),
+            cfs,
+            AccessType::ReadOnly {
+                error_if_log_file_exist,
+            },
+        )
+    }
+
+    fn open_cf_inner(
+        opts: &Options,
+        path: &Path,
+        mut cfs: Vec<ColumnFamilyDescriptor>,
+        access: AccessType,
+    ) -> Result<DB, Error> {
         let cpath = match CString::new(path.to_string_lossy().as_bytes()) {
             Ok(c) => c,
             Err(_) => {
@@ -664,54 +908,76 @@ impl DB {
             }
         };
 
-        if let Err(e) = fs::create_dir_all(&path) {
-            return Err(Error::new(format!(
-                "Failed to create RocksDB directory: `{:?}`.",
-                e
-            )));
+        if let AccessType::ReadWrite = access {
+            if let Err(e) = fs::create_dir_all(&path) {
+                return Err(Error::new(format!(
+                    "Failed to create RocksDB directory: `{:?}`.",
+                    e
+                )));
+            }
         }
 
         let db: *mut ffi::rocksdb_t;
         let cf_map = Arc::new(RwLock::new(BTreeMap::new()));
+        let mut default_prefix_len = opts.prefix_len;
 
         if cfs.is_empty() {
             unsafe {
-                db = ffi_try!(ffi::rocksdb_open(opts.inner, cpath.as_ptr() as *const _));
+                db = match access {
+                    AccessType::ReadWrite => {
+                        ffi_try!(ffi::rocksdb_open(opts.inner, cpath.as_ptr() as *const _))
+                    }
+                    AccessType::ReadOnly {
+                        error_if_log_file_exist,
+                    } => ffi_try!(ffi::rocksdb_open_for_read_only(
+                        opts.inner,
+                        cpath.as_ptr() as *const _,
+                        error_if_log_file_exist as c_uchar
+                    )),
+                };
             }
         } else {
-            let mut cfs_v = cfs.to_vec();
             // Always open the default column family.
-            if !cfs_v.contains(&"default") {
-                cfs_v.push("default");
+            if !cfs.iter().any(|cf| cf.name == "default") {
+                cfs.push(ColumnFamilyDescriptor::new("default", Options::default()));
             }
 
             // We need to store our CStrings in an intermediate vector
             // so that their pointers remain valid.
-            let c_cfs: Vec<CString> = cfs_v
+            let c_cfs: Vec<CString> = cfs
                 .iter()
-                .map(|cf| CString::new(cf.as_bytes()).unwrap())
+                .map(|cf| CString::new(cf.name.as_bytes()).unwrap())
                 .collect();
 
             let cfnames: Vec<_> = c_cfs.iter().map(|cf| cf.as_ptr()).collect();
 
             // These handles will be populated by DB.
-            let mut cfhandles: Vec<_> = cfs_v.iter().map(|_| ptr::null_mut()).collect();
+            let mut cfhandles: Vec<_> = cfs.iter().map(|_| ptr::null_mut()).collect();
 
-            // TODO(tyler) allow options to be passed in.
-            let cfopts: Vec<_> = cfs_v
-                .iter()
-                .map(|_| unsafe { ffi::rocksdb_options_create() as *const _ })
-                .collect();
+            let cfopts: Vec<_> = cfs.iter().map(|cf| cf.options.inner as *const _).collect();
 
             unsafe {
-                db = ffi_try!(ffi::rocksdb_open_column_families(
-                    opts.inner,
-                    cpath.as_ptr() as *const _,
-                    cfs_v.len() as c_int,
-                    cfnames.as_ptr() as *const _,
-                    cfopts.as_ptr(),
-                    cfhandles.as_mut_ptr()
-                ));
+                db = match access {
+                    AccessType::ReadWrite => ffi_try!(ffi::rocksdb_open_column_families(
+                        opts.inner,
+                        cpath.as_ptr() as *const _,
+                        cfs.len() as c_int,
+                        cfnames.as_ptr() as *const _,
+                        cfopts.as_ptr(),
+                        cfhandles.as_mut_ptr()
+                    )),
+                    AccessType::ReadOnly {
+                        error_if_log_file_exist,
+                    } => ffi_try!(ffi::rocksdb_open_for_read_only_column_families(
+                        opts.inner,
+                        cpath.as_ptr() as *const _,
+                        cfs.len() as c_int,
+                        cfnames.as_ptr() as *const _,
+                        cfopts.as_ptr(),
+                        cfhandles.as_mut_ptr(),
+                        error_if_log_file_exist as c_uchar
+                    )),
+                };
             }
 
             for handle in &cfhandles {
@@ -724,11 +990,15 @@ impl DB {
                 }
             }
 
-            for (n, h) in cfs_v.iter().zip(cfhandles) {
-                cf_map
-                    .write()
-                    .unwrap()
-                    .insert(n.to_string(), ColumnFamily { inner: h });
+            for (cf, h) in cfs.iter().zip(cfhandles) {
+                let handle = ColumnFamily {
+                    inner: h,
+                    prefix_len: cf.options.prefix_len,
+                };
+                cf_map.write().unwrap().insert(cf.name.clone(), handle);
+                if cf.name == "default" {
+                    default_prefix_len = cf.options.prefix_len;
+                }
             }
         }
 
@@ -740,6 +1010,9 @@ impl DB {
             inner: db,
             cfs: cf_map,
             path: path.to_path_buf(),
+            update_locks: (0..UPDATE_LOCK_STRIPES).map(|_| Mutex::new(())).collect(),
+            bulk_load_lock: Mutex::new(()),
+            default_prefix_len,
         })
     }
 
@@ -764,10 +1037,10 @@ impl DB {
     }
 
     pub fn write_opt(&self, batch: WriteBatch, writeopts: &WriteOptions) -> Result<(), Error> {
-        unsafe {
+        instrument(self, "write", None, None, || unsafe {
             ffi_try!(ffi::rocksdb_write(self.inner, writeopts.inner, batch.inner));
-        }
-        Ok(())
+            Ok(())
+        })
     }
 
     pub fn write(&self, batch: WriteBatch) -> Result<(), Error> {
@@ -792,7 +1065,7 @@ impl DB {
             ));
         }
 
-        unsafe {
+        instrument(self, "get", None, Some(key.len()), || unsafe {
             let mut val_len: size_t = 0;
             let val = ffi_try!(ffi::rocksdb_get(
                 self.inner,
@@ -806,7 +1079,7 @@ impl DB {
             } else {
                 Ok(Some(DBVector::from_c(val, val_len)))
             }
-        }
+        })
     }
 
     /// Return the bytes associated with a key value
@@ -831,7 +1104,7 @@ impl DB {
             ));
         }
 
-        unsafe {
+        instrument(self, "get", Some(cf), Some(key.len()), || unsafe {
             let mut val_len: size_t = 0;
             let val = ffi_try!(ffi::rocksdb_get_cf(
                 self.inner,
@@ -846,7 +1119,7 @@ impl DB {
             } else {
                 Ok(Some(DBVector::from_c(val, val_len)))
             }
-        }
+        })
     }
 
     pub fn get_cf(&self, cf: ColumnFamily, key: &[u8]) -> Result<Option<DBVector>, Error> {
@@ -854,6 +1127,29 @@ impl DB {
     }
 
     pub fn create_cf(&self, name: &str, opts: &Options) -> Result<ColumnFamily, Error> {
+        let mut cfs = self.cfs.write().unwrap();
+        self.create_cf_inner(&mut cfs, name, opts)
+    }
+
+    /// Returns the handle of the column family `name`, creating the column family
+    /// with `opts` if it doesn't exist.
+    ///
+    /// The check and the creation are atomic with respect to other calls of this method
+    /// and `create_cf`, so concurrent callers all get the same handle.
+    pub fn get_or_create_cf(&self, name: &str, opts: &Options) -> Result<ColumnFamily, Error> {
+        let mut cfs = self.cfs.write().unwrap();
+        if let Some(cf) = cfs.get(name) {
+            return Ok(*cf);
+        }
+        self.create_cf_inner(&mut cfs, name, opts)
+    }
+
+    fn create_cf_inner(
+        &self,
+        cfs: &mut BTreeMap<String, ColumnFamily>,
+        name: &str,
+        opts: &Options,
+    ) -> Result<ColumnFamily, Error> {
         let cname = match CString::new(name.as_bytes()) {
             Ok(c) => c,
             Err(_) => {
@@ -870,8 +1166,11 @@ impl DB {
                 opts.inner,
                 cname.as_ptr()
             ));
-            let cf = ColumnFamily { inner: cf_handler };
-            self.cfs.write().unwrap().insert(name.to_string(), cf);
+            let cf = ColumnFamily {
+                inner: cf_handler,
+                prefix_len: opts.prefix_len,
+            };
+            cfs.insert(name.to_string(), cf);
             cf
         };
         Ok(cf)
@@ -897,7 +1196,7 @@ impl DB {
 
     pub fn iterator(&self, mode: IteratorMode) -> DBIterator {
         let opts = ReadOptions::default();
-        DBIterator::new(self, &opts, mode)
+        DBIterator::new(self, opts, mode)
     }
 
     pub fn iterator_cf(
@@ -906,25 +1205,80 @@ impl DB {
         mode: IteratorMode,
     ) -> Result<DBIterator, Error> {
         let opts = ReadOptions::default();
-        DBIterator::new_cf(self, cf_handle, &opts, mode)
+        DBIterator::new_cf(self, cf_handle, opts, mode)
+    }
+
+    /// Returns an iterator over all keys starting with `prefix`, in lexicographic order.
+    ///
+    /// The iterator is bounded by the successor of the prefix, so it stops on its own
+    /// after the last matching key and no `starts_with` check is needed. If a prefix
+    /// extractor is configured (see `Options::set_fixed_prefix_extractor`) and `prefix`
+    /// is at least as long as the extracted prefixes, the prefix bloom filters are used
+    /// to skip files that can't contain matching keys. A shorter `prefix` spans several
+    /// extracted prefixes, so the iterator falls back to a total order seek then.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// extern crate tempdir;
+    /// extern crate exonum_rocksdb;
+    ///
+    /// use exonum_rocksdb::DB;
+    /// use tempdir::TempDir;
+    ///
+    /// # fn main() {
+    /// let temp_dir = TempDir::new("storage_prefix").unwrap();
+    /// let db = DB::open_default(temp_dir.path()).unwrap();
+    /// db.put(b"user:1", b"alice").unwrap();
+    /// db.put(b"user:2", b"bob").unwrap();
+    /// db.put(b"zone:1", b"eu").unwrap();
+    ///
+    /// let users: Vec<_> = db.prefix_iterator(b"user:").collect();
+    /// assert_eq!(users.len(), 2);
+    /// # }
+    /// ```
+    pub fn prefix_iterator(&self, prefix: &[u8]) -> DBIterator {
+        let opts = ReadOptions::for_prefix(prefix, self.default_prefix_len);
+        DBIterator::new(self, opts, IteratorMode::From(prefix, Direction::Forward))
+    }
+
+    /// Same as `prefix_iterator`, but for the given column family.
+    pub fn prefix_iterator_cf(
+        &self,
+        cf_handle: ColumnFamily,
+        prefix: &[u8],
+    ) -> Result<DBIterator, Error> {
+        let opts = ReadOptions::for_prefix(prefix, cf_handle.prefix_len);
+        DBIterator::new_cf(
+            self,
+            cf_handle,
+            opts,
+            IteratorMode::From(prefix, Direction::Forward),
+        )
     }
 
     pub fn raw_iterator(&self) -> DBRawIterator {
         let opts = ReadOptions::default();
-        DBRawIterator::new(self, &opts)
+        DBRawIterator::new(self, opts)
     }
 
     pub fn raw_iterator_cf(&self, cf_handle: ColumnFamily) -> Result<DBRawIterator, Error> {
         let opts = ReadOptions::default();
-        DBRawIterator::new_cf(self, cf_handle, &opts)
+        DBRawIterator::new_cf(self, cf_handle, opts)
     }
 
     pub fn snapshot(&self) -> Snapshot {
         Snapshot::new(self)
     }
 
+    /// Returns a watcher of the changes of the keys starting with `prefix` in the default
+    /// column family, committed after this call. See the `watch` module for details.
+    pub fn watch_prefix(&self, prefix: &[u8]) -> PrefixWatcher {
+        PrefixWatcher::new(self, prefix)
+    }
+
     pub fn put_opt(&self, key: &[u8], value: &[u8], writeopts: &WriteOptions) -> Result<(), Error> {
-        unsafe {
+        instrument(self, "put", None, Some(key.len()), || unsafe {
             ffi_try!(ffi::rocksdb_put(
                 self.inner,
                 writeopts.inner,
@@ -934,7 +1288,7 @@ impl DB {
                 value.len() as size_t
             ));
             Ok(())
-        }
+        })
     }
 
     pub fn put_cf_opt(
@@ -944,7 +1298,7 @@ impl DB {
         value: &[u8],
         writeopts: &WriteOptions,
     ) -> Result<(), Error> {
-        unsafe {
+        instrument(self, "put", Some(cf), Some(key.len()), || unsafe {
             ffi_try!(ffi::rocksdb_put_cf(
                 self.inner,
                 writeopts.inner,
@@ -955,7 +1309,7 @@ impl DB {
                 value.len() as size_t
             ));
             Ok(())
-        }
+        })
     }
 
     pub fn merge_opt(
@@ -964,7 +1318,7 @@ impl DB {
         value: &[u8],
         writeopts: &WriteOptions,
     ) -> Result<(), Error> {
-        unsafe {
+        instrument(self, "merge", None, Some(key.len()), || unsafe {
             ffi_try!(ffi::rocksdb_merge(
                 self.inner,
                 writeopts.inner,
@@ -974,7 +1328,7 @@ impl DB {
                 value.len() as size_t
             ));
             Ok(())
-        }
+        })
     }
 
     pub fn merge_cf_opt(
@@ -984,7 +1338,7 @@ impl DB {
         value: &[u8],
         writeopts: &WriteOptions,
     ) -> Result<(), Error> {
-        unsafe {
+        instrument(self, "merge", Some(cf), Some(key.len()), || unsafe {
             ffi_try!(ffi::rocksdb_merge_cf(
                 self.inner,
                 writeopts.inner,
@@ -995,11 +1349,11 @@ impl DB {
                 value.len() as size_t
             ));
             Ok(())
-        }
+        })
     }
 
     pub fn delete_opt(&self, key: &[u8], writeopts: &WriteOptions) -> Result<(), Error> {
-        unsafe {
+        instrument(self, "delete", None, Some(key.len()), || unsafe {
             ffi_try!(ffi::rocksdb_delete(
                 self.inner,
                 writeopts.inner,
@@ -1007,7 +1361,7 @@ impl DB {
                 key.len() as size_t
             ));
             Ok(())
-        }
+        })
     }
 
     pub fn delete_cf_opt(
@@ -1016,7 +1370,7 @@ impl DB {
         key: &[u8],
         writeopts: &WriteOptions,
     ) -> Result<(), Error> {
-        unsafe {
+        instrument(self, "delete", Some(cf), Some(key.len()), || unsafe {
             ffi_try!(ffi::rocksdb_delete_cf(
                 self.inner,
                 writeopts.inner,
@@ -1025,7 +1379,7 @@ impl DB {
                 key.len() as size_t
             ));
             Ok(())
-        }
+        })
     }
 
     pub fn put(&self, key: &[u8], value: &[u8]) -> Result<(), Error> {
@@ -1052,6 +1406,264 @@ impl DB {
         self.delete_cf_opt(cf, key, &WriteOptions::default())
     }
 
+    /// Replaces the value stored under `key` with the result of `f`.
+    ///
+    /// `f` receives the current value, or `None` if the key is absent, and returns
+    /// the new value, or `None` to delete the key. The closure is run without any
+    /// lock held; the value is then re-read under a lock and, if another `update`
+    /// has changed it in the meantime, `f` is called again with the fresh value.
+    /// Hence `f` may be invoked more than once.
+    ///
+    /// The lock is one of a fixed set of locks picked by the column family and the key,
+    /// so updates of different keys rarely wait for each other. Only concurrent
+    /// `update` and `update_cf` calls are serialized against each other, plain writes
+    /// such as `put` or `write` are not.
+    ///
+    /// `f` is always applied eagerly, even if the column family has a merge operator.
+    /// For commutative updates like counters, writing merge operands with `merge`
+    /// instead is cheaper as it avoids the read altogether.
+    ///
+    /// Returns the value which has been written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// extern crate tempdir;
+    /// extern crate exonum_rocksdb;
+    ///
+    /// use exonum_rocksdb::DB;
+    /// use tempdir::TempDir;
+    ///
+    /// # fn main() {
+    /// let temp_dir = TempDir::new("storage_update").unwrap();
+    /// let db = DB::open_default(temp_dir.path()).unwrap();
+    ///
+    /// // Append a byte to the value, creating it if necessary
+    /// db.update(b"my key", |old| {
+    ///     let mut value = old.map(|v| v.to_vec()).unwrap_or_default();
+    ///     value.push(b'x');
+    ///     Some(value)
+    /// }).unwrap();
+    /// assert_eq!(&*db.get(b"my key").unwrap().unwrap(), b"x");
+    /// # }
+    /// ```
+    pub fn update<F>(&self, key: &[u8], f: F) -> Result<Option<Vec<u8>>, Error>
+    where
+        F: FnMut(Option<&[u8]>) -> Option<Vec<u8>>,
+    {
+        self.update_inner(None, key, f)
+    }
+
+    /// Same as `update`, but for the given column family.
+    pub fn update_cf<F>(&self, cf: ColumnFamily, key: &[u8], f: F) -> Result<Option<Vec<u8>>, Error>
+    where
+        F: FnMut(Option<&[u8]>) -> Option<Vec<u8>>,
+    {
+        self.update_inner(Some(cf), key, f)
+    }
+
+    fn update_inner<F>(
+        &self,
+        cf: Option<ColumnFamily>,
+        key: &[u8],
+        mut f: F,
+    ) -> Result<Option<Vec<u8>>, Error>
+    where
+        F: FnMut(Option<&[u8]>) -> Option<Vec<u8>>,
+    {
+        let read = |db: &DB| match cf {
+            Some(cf) => db.get_cf(cf, key),
+            None => db.get(key),
+        };
+
+        let mut current = read(self)?;
+        loop {
+            let new_value = f(current.as_ref().map(|v| &v[..]));
+
+            let _guard = self.update_lock(cf, key).lock().unwrap();
+            let latest = read(self)?;
+            if latest.as_ref().map(|v| &v[..]) != current.as_ref().map(|v| &v[..]) {
+                // Lost the race against a concurrent update, retry with the new value.
+                current = latest;
+                continue;
+            }
+
+            match (cf, new_value.as_ref()) {
+                (Some(cf), Some(value)) => self.put_cf(cf, key, value)?,
+                (Some(cf), None) => self.delete_cf(cf, key)?,
+                (None, Some(value)) => self.put(key, value)?,
+                (None, None) => self.delete(key)?,
+            }
+            return Ok(new_value);
+        }
+    }
+
+    /// Returns the lock serializing `update` calls for `key` in the column family `cf`.
+    fn update_lock(&self, cf: Option<ColumnFamily>, key: &[u8]) -> &Mutex<()> {
+        // `update` and `update_cf` with the default column family handle have to share
+        // the lock for the same key.
+        let cf = cf.or_else(|| self.cfs.read().unwrap().get("default").cloned());
+        let mut hasher = DefaultHasher::new();
+        cf.map(|cf| cf.inner as usize).hash(&mut hasher);
+        key.hash(&mut hasher);
+        &self.update_locks[hasher.finish() as usize % self.update_locks.len()]
+    }
+
+    /// Returns `true` if none of the column families of the database contains any keys.
+    ///
+    /// Unlike `estimate_num_keys`, the result is exact, yet cheap to compute, since at most
+    /// one key per column family is read.
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        for (_, cf) in self.column_families() {
+            let mut iter = match cf {
+                Some(cf) => self.raw_iterator_cf(cf)?,
+                None => self.raw_iterator(),
+            };
+            iter.seek_to_first();
+            iter.status()?;
+            if iter.valid() {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns `true` if the column family contains no keys.
+    pub fn is_empty_cf(&self, cf: ColumnFamily) -> Result<bool, Error> {
+        let mut iter = self.raw_iterator_cf(cf)?;
+        iter.seek_to_first();
+        iter.status()?;
+        Ok(!iter.valid())
+    }
+
+    /// Returns the estimated number of keys in all column families of the database.
+    ///
+    /// The estimate is the sum of the `rocksdb.estimate-num-keys` properties of the column
+    /// families and may be off considerably if there are many overwrites or deletions.
+    pub fn estimate_num_keys(&self) -> Result<u64, Error> {
+        let mut num_keys = 0;
+        for (_, cf) in self.column_families() {
+            num_keys += self.int_property_or_zero(cf, "rocksdb.estimate-num-keys")?;
+        }
+        Ok(num_keys)
+    }
+
+    /// Returns the estimated number of keys in the column family.
+    pub fn estimate_num_keys_cf(&self, cf: ColumnFamily) -> Result<u64, Error> {
+        self.property_int_value_cf(cf, "rocksdb.estimate-num-keys")
+            .map(|value| value.unwrap_or(0))
+    }
+
+    /// Retrieves a `RocksDB` property by name, such as `rocksdb.stats`.
+    ///
+    /// Returns `None` if the property is not known to `RocksDB`. See
+    /// [the list of properties](https://github.com/facebook/rocksdb/blob/master/include/rocksdb/db.h)
+    /// supported by the database.
+    pub fn property_value(&self, name: &str) -> Result<Option<String>, Error> {
+        self.property_value_inner(None, name)
+    }
+
+    /// Retrieves a `RocksDB` property of the column family by name.
+    pub fn property_value_cf(&self, cf: ColumnFamily, name: &str) -> Result<Option<String>, Error> {
+        self.property_value_inner(Some(cf), name)
+    }
+
+    /// Retrieves a numeric `RocksDB` property by name, such as `rocksdb.estimate-num-keys`.
+    pub fn property_int_value(&self, name: &str) -> Result<Option<u64>, Error> {
+        self.property_value(name)
+            .and_then(|value| parse_property_int(name, value))
+    }
+
+    /// Retrieves a numeric `RocksDB` property of the column family by name.
+    pub fn property_int_value_cf(
+        &self,
+        cf: ColumnFamily,
+        name: &str,
+    ) -> Result<Option<u64>, Error> {
+        self.property_value_cf(cf, name)
+            .and_then(|value| parse_property_int(name, value))
+    }
+
+    /// Reports the disk space used by the database.
+    ///
+    /// SST and blob sizes are taken from the `rocksdb.live-sst-files-size` and
+    /// `rocksdb.live-blob-file-size` properties of each column family; the bundled
+    /// RocksDB does not have the latter, so blob sizes are always zero. The WAL sizes
+    /// are the total sizes of the `*.log` files in the database directory and in its
+    /// `archive` subdirectory; WAL files placed in a separate directory are not counted.
+    pub fn disk_usage(&self) -> Result<DiskUsage, Error> {
+        let mut usage = DiskUsage::default();
+        for (name, cf) in self.column_families() {
+            let cf_usage = ColumnFamilyDiskUsage {
+                sst_bytes: self.int_property_or_zero(cf, "rocksdb.live-sst-files-size")?,
+                blob_bytes: self.int_property_or_zero(cf, "rocksdb.live-blob-file-size")?,
+            };
+            usage.sst_bytes += cf_usage.sst_bytes;
+            usage.blob_bytes += cf_usage.blob_bytes;
+            usage.column_families.insert(name, cf_usage);
+        }
+
+        usage.wal_bytes = log_files_size(&self.path)?;
+        // The archive is only created once the first WAL file is archived.
+        let archive = self.path.join("archive");
+        if archive.is_dir() {
+            usage.archived_wal_bytes = log_files_size(&archive)?;
+        }
+
+        Ok(usage)
+    }
+
+    /// Lists names of all column families of the database with their handles;
+    /// the default column family comes first and has no handle.
+    fn column_families(&self) -> Vec<(String, Option<ColumnFamily>)> {
+        // The default column family is not registered in `cfs` if the database
+        // has been opened without column families.
+        let mut cfs = vec![("default".to_owned(), None)];
+        for (name, cf) in self.cfs.read().unwrap().iter() {
+            if name != "default" {
+                cfs.push((name.clone(), Some(*cf)));
+            }
+        }
+        cfs
+    }
+
+    fn int_property_or_zero(&self, cf: Option<ColumnFamily>, name: &str) -> Result<u64, Error> {
+        self.property_value_inner(cf, name)
+            .and_then(|value| parse_property_int(name, value))
+            .map(|value| value.unwrap_or(0))
+    }
+
+    fn property_value_inner(
+        &self,
+        cf: Option<ColumnFamily>,
+        name: &str,
+    ) -> Result<Option<String>, Error> {
+        let prop_name = match CString::new(name) {
+            Ok(c) => c,
+            Err(_) => {
+                return Err(Error::new(format!(
+                    "Failed to convert property name to CString: {}",
+                    name
+                )))
+            }
+        };
+
+        unsafe {
+            let value = match cf {
+                Some(cf) => {
+                    ffi::rocksdb_property_value_cf(self.inner, cf.inner, prop_name.as_ptr())
+                }
+                None => ffi::rocksdb_property_value(self.inner, prop_name.as_ptr()),
+            };
+            if value.is_null() {
+                return Ok(None);
+            }
+            let s = CStr::from_ptr(value).to_string_lossy().into_owned();
+            ffi::rocksdb_free(value as *mut c_void);
+            Ok(Some(s))
+        }
+    }
+
     pub fn compact_range(&self, start: Option<&[u8]>, end: Option<&[u8]>) {
         unsafe {
             ffi::rocksdb_compact_range(
@@ -1210,20 +1822,26 @@ impl ReadOptions {
     // TODO add snapshot setting here
     // TODO add snapshot wrapper structs with proper destructors;
     // that struct needs an "iterator" impl too.
-    #[allow(dead_code)]
     fn fill_cache(&mut self, v: bool) {
         unsafe {
             ffi::rocksdb_readoptions_set_fill_cache(self.inner, v as c_uchar);
         }
+        self.fill_cache = v;
     }
 
     pub fn set_snapshot<T: Inner>(&mut self, snapshot: &T) {
+        self.set_snapshot_inner(snapshot.get_inner());
+    }
+
+    fn set_snapshot_inner(&mut self, snapshot: *const ffi::rocksdb_snapshot_t) {
         unsafe {
-            ffi::rocksdb_readoptions_set_snapshot(self.inner, snapshot.get_inner());
+            ffi::rocksdb_readoptions_set_snapshot(self.inner, snapshot);
         }
+        self.snapshot = Some(snapshot);
     }
 
     pub fn set_iterate_upper_bound(&mut self, key: &[u8]) {
+        let key = key.to_vec();
         unsafe {
             ffi::rocksdb_readoptions_set_iterate_upper_bound(
                 self.inner,
@@ -1231,6 +1849,32 @@ impl ReadOptions {
                 key.len() as size_t,
             );
         }
+        self.iterate_upper_bound = Some(key);
+    }
+
+    /// Makes iterators ignore the prefix extractor and seek over all keys in order,
+    /// which is required to iterate over keys with different extracted prefixes.
+    ///
+    /// Default: `false`
+    pub fn set_total_order_seek(&mut self, v: bool) {
+        unsafe {
+            ffi::rocksdb_readoptions_set_total_order_seek(self.inner, v as c_uchar);
+        }
+        self.total_order_seek = v;
+    }
+
+    /// Creates read options with the upper bound set right past the keys starting
+    /// with `prefix`, for a column family with the prefix extractor length `prefix_len`.
+    fn for_prefix(prefix: &[u8], prefix_len: Option<usize>) -> ReadOptions {
+        let mut opts = ReadOptions::default();
+        // Prefix seek mode is only defined for keys sharing the extracted prefix.
+        if prefix_len.map_or(false, |len| prefix.len() < len) {
+            opts.set_total_order_seek(true);
+        }
+        if let Some(upper_bound) = prefix_successor(prefix) {
+            opts.set_iterate_upper_bound(&upper_bound);
+        }
+        opts
     }
 }
 
@@ -1239,9 +1883,87 @@ impl Default for ReadOptions {
         unsafe {
             ReadOptions {
                 inner: ffi::rocksdb_readoptions_create(),
+                fill_cache: true,
+                snapshot: None,
+                iterate_upper_bound: None,
+                total_order_seek: false,
+            }
+        }
+    }
+}
+
+impl Clone for ReadOptions {
+    fn clone(&self) -> ReadOptions {
+        let mut opts = ReadOptions::default();
+        if !self.fill_cache {
+            opts.fill_cache(false);
+        }
+        if let Some(snapshot) = self.snapshot {
+            opts.set_snapshot_inner(snapshot);
+        }
+        if let Some(ref upper_bound) = self.iterate_upper_bound {
+            opts.set_iterate_upper_bound(upper_bound);
+        }
+        if self.total_order_seek {
+            opts.set_total_order_seek(true);
+        }
+        opts
+    }
+}
+
+impl fmt::Debug for ReadOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReadOptions")
+            .field("fill_cache", &self.fill_cache)
+            .field("snapshot", &self.snapshot.is_some())
+            .field("iterate_upper_bound", &self.iterate_upper_bound)
+            .field("total_order_seek", &self.total_order_seek)
+            .finish()
+    }
+}
+
+fn parse_property_int(name: &str, value: Option<String>) -> Result<Option<u64>, Error> {
+    match value {
+        Some(value) => match value.trim().parse::<u64>() {
+            Ok(n) => Ok(Some(n)),
+            Err(_) => Err(Error::new(format!(
+                "Property {} is not a number: {}",
+                name, value
+            ))),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Returns the total size of the `*.log` files in `dir`.
+fn log_files_size(dir: &Path) -> Result<u64, Error> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| Error::new(format!("Failed to read RocksDB directory: `{:?}`.", e)))?;
+    let mut size = 0;
+    for entry in entries {
+        let entry = entry
+            .map_err(|e| Error::new(format!("Failed to read RocksDB directory: `{:?}`.", e)))?;
+        if entry.path().extension().map_or(false, |ext| ext == "log") {
+            // The file may be deleted concurrently once the WAL is no longer needed.
+            if let Ok(metadata) = entry.metadata() {
+                size += metadata.len();
             }
         }
     }
+    Ok(size)
+}
+
+/// Returns the smallest key which is greater than all keys starting with `prefix`,
+/// or `None` if there is no such key (i.e., the prefix is empty or consists of `0xff` bytes).
+fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut successor = prefix.to_vec();
+    while let Some(last) = successor.pop() {
+        if last != u8::max_value() {
+            successor.push(last + 1);
+            return Some(successor);
+        }
+    }
+    None
 }
 
 /// Vector of bytes stored in the database.
@@ -1300,6 +2022,14 @@ impl DBVector {
     }
 }
 
+#[test]
+fn test_prefix_successor() {
+    assert_eq!(prefix_successor(b"abc"), Some(b"abd".to_vec()));
+    assert_eq!(prefix_successor(b"ab\xff"), Some(b"ac".to_vec()));
+    assert_eq!(prefix_successor(b"\xff\xff"), None);
+    assert_eq!(prefix_successor(b""), None);
+}
+
 #[test]
 fn test_db_vector() {
     use libc::calloc;
@@ -16,14 +16,17 @@
 use compaction_filter::{self, filter_callback, CompactionFilterCallback, CompactionFilterFn};
 use comparator::{self, ComparatorCallback, CompareFn};
 use ffi;
-use {BlockBasedOptions, DBCompactionStyle, DBCompressionType, Options, WriteOptions};
+use ffi_util::error_message;
+use {BlockBasedOptions, DBCompactionStyle, DBCompressionType, Env, Options, WriteOptions};
 
-use libc::{c_int, c_uchar, c_uint, c_void, size_t};
+use libc::{c_char, c_int, c_uchar, c_uint, c_void, size_t};
 use merge_operator::{
     self, full_merge_callback, partial_merge_callback, MergeFn, MergeOperatorCallback,
 };
 use std::ffi::{CStr, CString};
+use std::fmt;
 use std::mem;
+use std::ptr;
 
 pub fn new_cache(capacity: size_t) -> *mut ffi::rocksdb_cache_t {
     unsafe { ffi::rocksdb_cache_create_lru(capacity) }
@@ -53,6 +56,14 @@ impl Drop for WriteOptions {
     }
 }
 
+impl Drop for Env {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_env_destroy(self.inner);
+        }
+    }
+}
+
 impl BlockBasedOptions {
     pub fn set_block_size(&mut self, size: usize) {
         unsafe {
@@ -287,6 +298,30 @@ impl Options {
         self.set_comparator(name, compare_fn);
     }
 
+    /// Sets a prefix extractor which takes the first `prefix_len` bytes of a key.
+    ///
+    /// With a prefix extractor in place, bloom filters are built over key prefixes,
+    /// so that seeks within a prefix (e.g., via `DB::prefix_iterator`) can skip files
+    /// which do not contain it. Keys shorter than `prefix_len` are not covered by the
+    /// filters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use exonum_rocksdb::Options;
+    ///
+    /// let mut opts = Options::default();
+    /// opts.set_fixed_prefix_extractor(8);
+    /// ```
+    pub fn set_fixed_prefix_extractor(&mut self, prefix_len: usize) {
+        unsafe {
+            // The options take ownership of the slice transform.
+            let transform = ffi::rocksdb_slicetransform_create_fixed_prefix(prefix_len);
+            ffi::rocksdb_options_set_prefix_extractor(self.inner, transform);
+        }
+        self.prefix_len = Some(prefix_len);
+    }
+
     pub fn optimize_for_point_lookup(&mut self, cache_size: u64) {
         unsafe {
             ffi::rocksdb_options_optimize_for_point_lookup(self.inner, cache_size);
@@ -535,6 +570,46 @@ impl Options {
         }
     }
 
+    /// Sets the maximum buffer size used by the writable file wrappers (e.g., for WAL
+    /// and SST files); writes are buffered up to this size before being issued to the OS.
+    ///
+    /// Default: `1048576` (1 MB)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use exonum_rocksdb::Options;
+    ///
+    /// let mut opts = Options::default();
+    /// opts.set_writable_file_max_buffer_size(4 * 1024 * 1024);
+    /// ```
+    pub fn set_writable_file_max_buffer_size(&mut self, nbytes: u64) {
+        unsafe {
+            ffi::rocksdb_options_set_writable_file_max_buffer_size(self.inner, nbytes);
+        }
+    }
+
+    /// If true, then `DB::open` will not update the statistics used to optimize
+    /// compaction decisions by loading table properties from many files.
+    /// Turning off this feature will improve the open time, especially for
+    /// databases with a large number of SST files on slow storage.
+    ///
+    /// Default: `false`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use exonum_rocksdb::Options;
+    ///
+    /// let mut opts = Options::default();
+    /// opts.set_skip_stats_update_on_db_open(true);
+    /// ```
+    pub fn set_skip_stats_update_on_db_open(&mut self, skip: bool) {
+        unsafe {
+            ffi::rocksdb_options_set_skip_stats_update_on_db_open(self.inner, skip as c_uchar);
+        }
+    }
+
     /// Sets the target file size for compaction.
     /// target_file_size_base is per-file size for level-1.
     /// Target file size for level L can be calculated by
@@ -830,6 +905,113 @@ impl Options {
             ffi::rocksdb_options_set_num_levels(self.inner, n);
         }
     }
+
+    /// Sets the time for which archived WAL files are kept before being deleted.
+    /// If both this and `set_wal_size_limit_mb` are zero, WAL files are deleted
+    /// as soon as they are no longer needed for recovery.
+    ///
+    /// Keeping WAL files is required to read changes with `DB::watch_prefix`.
+    ///
+    /// Default: `0`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use exonum_rocksdb::Options;
+    ///
+    /// let mut opts = Options::default();
+    /// opts.set_wal_ttl_seconds(3600);
+    /// ```
+    pub fn set_wal_ttl_seconds(&mut self, secs: u64) {
+        unsafe {
+            ffi::rocksdb_options_set_WAL_ttl_seconds(self.inner, secs);
+        }
+    }
+
+    /// Sets the total size of archived WAL files above which the oldest ones are deleted.
+    /// See `set_wal_ttl_seconds`.
+    ///
+    /// Default: `0`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use exonum_rocksdb::Options;
+    ///
+    /// let mut opts = Options::default();
+    /// opts.set_wal_size_limit_mb(1024);
+    /// ```
+    pub fn set_wal_size_limit_mb(&mut self, size: u64) {
+        unsafe {
+            ffi::rocksdb_options_set_WAL_size_limit_MB(self.inner, size);
+        }
+    }
+
+    /// Sets the environment used by the database.
+    ///
+    /// Default: `Env::default()`
+    pub fn set_env(&mut self, env: &Env) {
+        unsafe {
+            ffi::rocksdb_options_set_env(self.inner, env.inner);
+        }
+    }
+
+    /// By default, memtables containing data not persisted in the WAL (i.e., written
+    /// with the WAL disabled) are flushed when the database is closed. If true, the flush
+    /// is skipped to speed up closing the database, and such data is lost.
+    ///
+    /// Default: `false`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use exonum_rocksdb::Options;
+    ///
+    /// let mut opts = Options::default();
+    /// opts.set_avoid_flush_during_shutdown(true);
+    /// ```
+    pub fn set_avoid_flush_during_shutdown(&mut self, avoid: bool) {
+        self.set_from_string(&format!("avoid_flush_during_shutdown={}", avoid));
+    }
+
+    /// By default, the WAL is replayed and flushed on open, which may create very small
+    /// SST files. If true, the flush is avoided where possible (but not guaranteed to be)
+    /// and the WAL files are kept, so the data can be recovered from them again.
+    ///
+    /// Default: `false`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use exonum_rocksdb::Options;
+    ///
+    /// let mut opts = Options::default();
+    /// opts.set_avoid_flush_during_recovery(true);
+    /// ```
+    pub fn set_avoid_flush_during_recovery(&mut self, avoid: bool) {
+        self.set_from_string(&format!("avoid_flush_during_recovery={}", avoid));
+    }
+
+    /// Sets options which have no setters in the C API, replacing `inner` with
+    /// a copy of it updated according to `opts_str`.
+    fn set_from_string(&mut self, opts_str: &str) {
+        let c_opts = CString::new(opts_str).unwrap();
+        unsafe {
+            let new_inner = ffi::rocksdb_options_create();
+            let mut err: *mut c_char = ptr::null_mut();
+            ffi::rocksdb_get_options_from_string(self.inner, c_opts.as_ptr(), new_inner, &mut err);
+            if !err.is_null() {
+                ffi::rocksdb_options_destroy(new_inner);
+                panic!(
+                    "Could not set RocksDB options `{}`: {}",
+                    opts_str,
+                    error_message(err)
+                );
+            }
+            ffi::rocksdb_options_destroy(self.inner);
+            self.inner = new_inner;
+        }
+    }
 }
 
 impl Default for Options {
@@ -839,7 +1021,10 @@ impl Default for Options {
             if opts.is_null() {
                 panic!("Could not create RocksDB options");
             }
-            Options { inner: opts }
+            Options {
+                inner: opts,
+                prefix_len: None,
+            }
         }
     }
 }
@@ -853,12 +1038,74 @@ impl WriteOptions {
         unsafe {
             ffi::rocksdb_writeoptions_set_sync(self.inner, sync as c_uchar);
         }
+        self.sync = sync;
     }
 
     pub fn disable_wal(&mut self, disable: bool) {
         unsafe {
             ffi::rocksdb_writeoptions_disable_WAL(self.inner, disable as c_int);
         }
+        self.disable_wal = disable;
+    }
+}
+
+impl Env {
+    /// Sets the number of threads in the low priority pool, which runs compactions.
+    pub fn set_background_threads(&mut self, n: c_int) {
+        unsafe {
+            ffi::rocksdb_env_set_background_threads(self.inner, n);
+        }
+    }
+
+    /// Sets the number of threads in the high priority pool, which runs flushes.
+    pub fn set_high_priority_background_threads(&mut self, n: c_int) {
+        unsafe {
+            ffi::rocksdb_env_set_high_priority_background_threads(self.inner, n);
+        }
+    }
+
+    /// Lowers the IO priority of the threads in the low priority pool.
+    /// Only has effect on Linux.
+    pub fn lower_thread_pool_io_priority(&mut self) {
+        unsafe {
+            ffi::rocksdb_env_lower_thread_pool_io_priority(self.inner);
+        }
+    }
+
+    /// Lowers the IO priority of the threads in the high priority pool.
+    /// Only has effect on Linux.
+    pub fn lower_high_priority_thread_pool_io_priority(&mut self) {
+        unsafe {
+            ffi::rocksdb_env_lower_high_priority_thread_pool_io_priority(self.inner);
+        }
+    }
+
+    /// Lowers the CPU priority of the threads in the low priority pool.
+    /// Only has effect on Linux.
+    pub fn lower_thread_pool_cpu_priority(&mut self) {
+        unsafe {
+            ffi::rocksdb_env_lower_thread_pool_cpu_priority(self.inner);
+        }
+    }
+
+    /// Lowers the CPU priority of the threads in the high priority pool.
+    /// Only has effect on Linux.
+    pub fn lower_high_priority_thread_pool_cpu_priority(&mut self) {
+        unsafe {
+            ffi::rocksdb_env_lower_high_priority_thread_pool_cpu_priority(self.inner);
+        }
+    }
+}
+
+impl Default for Env {
+    fn default() -> Env {
+        unsafe {
+            let env = ffi::rocksdb_create_default_env();
+            if env.is_null() {
+                panic!("Could not create RocksDB environment");
+            }
+            Env { inner: env }
+        }
     }
 }
 
@@ -868,7 +1115,29 @@ impl Default for WriteOptions {
         if write_opts.is_null() {
             panic!("Could not create RocksDB write options");
         }
-        WriteOptions { inner: write_opts }
+        WriteOptions {
+            inner: write_opts,
+            sync: false,
+            disable_wal: false,
+        }
+    }
+}
+
+impl Clone for WriteOptions {
+    fn clone(&self) -> WriteOptions {
+        let mut opts = WriteOptions::default();
+        opts.set_sync(self.sync);
+        opts.disable_wal(self.disable_wal);
+        opts
+    }
+}
+
+impl fmt::Debug for WriteOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WriteOptions")
+            .field("sync", &self.sync)
+            .field("disable_wal", &self.disable_wal)
+            .finish()
     }
 }
 
@@ -0,0 +1,204 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Optional instrumentation of database operations.
+//!
+//! With the `tracing` feature enabled, every operation is wrapped into a `rocksdb` span
+//! at the `DEBUG` level with the following fields:
+//!
+//! - `op`: name of the operation (`get`, `put`, `merge`, `delete`, `write`, `seek`)
+//! - `cf`: name of the column family
+//! - `key_size`: size of the key in bytes, for single-key operations
+//! - `duration_us`: duration of the operation in microseconds
+//! - `error`: error message, if the operation failed
+//!
+//! With the `metrics` feature enabled, the following metrics labeled with `op`
+//! are recorded through the `metrics` crate:
+//!
+//! - `rocksdb_operation_duration_seconds`: histogram of operation latencies
+//! - `rocksdb_operation_errors_total`: counter of failed operations
+//!
+//! For iterators, every seek is recorded as a separate `seek` operation; stepping
+//! through the keys with `next` and `prev` is not instrumented. Without either feature
+//! the instrumentation compiles to nothing.
+
+use {ColumnFamily, Error, DB};
+
+#[cfg(feature = "tracing")]
+use std::collections::BTreeMap;
+#[cfg(feature = "tracing")]
+use std::sync::{Arc, RwLock};
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+use std::time::Instant;
+
+#[cfg(feature = "tracing")]
+type ColumnFamilies = Arc<RwLock<BTreeMap<String, ColumnFamily>>>;
+
+/// Runs the database operation `f`, instrumenting it if enabled.
+#[inline]
+pub fn instrument<T, F>(
+    db: &DB,
+    op: &'static str,
+    cf: Option<ColumnFamily>,
+    key_size: Option<usize>,
+    f: F,
+) -> Result<T, Error>
+where
+    F: FnOnce() -> Result<T, Error>,
+{
+    let operation = Operation::start(db, op, cf, key_size);
+    let result = operation.run(f);
+    if let Err(ref e) = result {
+        operation.fail(e);
+    }
+    result
+}
+
+/// An instrumented operation, finished when dropped.
+pub struct Operation {
+    #[cfg(feature = "metrics")]
+    op: &'static str,
+    #[cfg(feature = "tracing")]
+    span: ::tracing::Span,
+    #[cfg(any(feature = "tracing", feature = "metrics"))]
+    start: Instant,
+}
+
+impl Operation {
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    #[inline]
+    pub fn start(
+        db: &DB,
+        op: &'static str,
+        cf: Option<ColumnFamily>,
+        key_size: Option<usize>,
+    ) -> Operation {
+        Operation {
+            #[cfg(feature = "metrics")]
+            op,
+            #[cfg(feature = "tracing")]
+            span: new_span(op, || cf_name(&db.cfs, cf), key_size),
+            #[cfg(any(feature = "tracing", feature = "metrics"))]
+            start: Instant::now(),
+        }
+    }
+
+    /// Runs `f` within the operation.
+    #[inline]
+    pub fn run<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        #[cfg(feature = "tracing")]
+        let _enter = self.span.enter();
+        f()
+    }
+
+    /// Marks the operation as failed with `error`.
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    #[inline]
+    pub fn fail(&self, error: &Error) {
+        #[cfg(feature = "tracing")]
+        self.span.record("error", &error.to_string().as_str());
+        #[cfg(feature = "metrics")]
+        counter!("rocksdb_operation_errors_total", 1, "op" => self.op);
+    }
+}
+
+/// Instrumentation of the seeks of an iterator.
+pub struct IteratorOperations {
+    // The column family name is only resolved for the spans which are enabled.
+    #[cfg(feature = "tracing")]
+    cfs: ColumnFamilies,
+    #[cfg(feature = "tracing")]
+    cf: Option<ColumnFamily>,
+}
+
+impl IteratorOperations {
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    #[inline]
+    pub fn new(db: &DB, cf: Option<ColumnFamily>) -> IteratorOperations {
+        IteratorOperations {
+            #[cfg(feature = "tracing")]
+            cfs: Arc::clone(&db.cfs),
+            #[cfg(feature = "tracing")]
+            cf,
+        }
+    }
+
+    /// Runs the seek `f`, instrumenting it if enabled.
+    #[inline]
+    pub fn seek<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        let operation = Operation {
+            #[cfg(feature = "metrics")]
+            op: "seek",
+            #[cfg(feature = "tracing")]
+            span: new_span("seek", || cf_name(&self.cfs, self.cf), None),
+            #[cfg(any(feature = "tracing", feature = "metrics"))]
+            start: Instant::now(),
+        };
+        operation.run(f)
+    }
+}
+
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+impl Drop for Operation {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        #[cfg(feature = "tracing")]
+        self.span.record(
+            "duration_us",
+            &(elapsed.as_secs() * 1_000_000 + u64::from(elapsed.subsec_micros())),
+        );
+        #[cfg(feature = "metrics")]
+        histogram!("rocksdb_operation_duration_seconds", elapsed, "op" => self.op);
+    }
+}
+
+#[cfg(feature = "tracing")]
+fn new_span<C>(op: &'static str, cf_name: C, key_size: Option<usize>) -> ::tracing::Span
+where
+    C: FnOnce() -> Option<String>,
+{
+    let span = debug_span!(
+        "rocksdb",
+        op,
+        cf = ::tracing::field::Empty,
+        key_size = ::tracing::field::Empty,
+        duration_us = ::tracing::field::Empty,
+        error = ::tracing::field::Empty
+    );
+    // Resolving the column family name may take a lock, so skip it if nobody listens.
+    if span.is_disabled() {
+        return span;
+    }
+
+    if let Some(name) = cf_name() {
+        span.record("cf", &name.as_str());
+    }
+    if let Some(key_size) = key_size {
+        span.record("key_size", &(key_size as u64));
+    }
+    span
+}
+
+#[cfg(feature = "tracing")]
+fn cf_name(cfs: &ColumnFamilies, cf: Option<ColumnFamily>) -> Option<String> {
+    match cf {
+        Some(cf) => {
+            let cfs = cfs.read().unwrap();
+            cfs.iter()
+                .find(|&(_, handle)| handle.inner == cf.inner)
+                .map(|(name, _)| name.clone())
+        }
+        None => Some("default".to_owned()),
+    }
+}
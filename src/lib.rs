@@ -40,23 +40,33 @@
 
 extern crate exonum_librocksdb_sys as ffi;
 extern crate libc;
+#[cfg(feature = "metrics")]
+#[macro_use]
+extern crate metrics;
 extern crate tempdir;
+#[cfg(feature = "tracing")]
+#[macro_use]
+extern crate tracing;
 
 #[macro_use]
 mod ffi_util;
 
 pub mod backup;
+pub mod bulk_load;
 pub mod compaction_filter;
 mod comparator;
 mod db;
 mod db_options;
+mod instrument;
 pub mod merge_operator;
 pub mod utils;
+pub mod watch;
 
 pub use compaction_filter::Decision as CompactionDecision;
 pub use db::{
-    new_bloom_filter, DBCompactionStyle, DBCompressionType, DBIterator, DBRawIterator,
-    DBRecoveryMode, DBVector, Direction, IteratorMode, ReadOptions, Snapshot, WriteBatch,
+    new_bloom_filter, ColumnFamilyDiskUsage, DBCompactionStyle, DBCompressionType, DBIterator,
+    DBRawIterator, DBRecoveryMode, DBVector, Direction, DiskUsage, IteratorMode, ReadOptions,
+    Snapshot, WriteBatch,
 };
 pub use merge_operator::MergeOperands;
 
@@ -64,7 +74,7 @@ use std::collections::BTreeMap;
 use std::error;
 use std::fmt;
 use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 
 /// A `RocksDB` database.
 ///
@@ -73,6 +83,11 @@ pub struct DB {
     inner: *mut ffi::rocksdb_t,
     cfs: Arc<RwLock<BTreeMap<String, ColumnFamily>>>,
     path: PathBuf,
+    update_locks: Vec<Mutex<()>>,
+    // Held by bulk loads while auto compactions are disabled for the ingestion.
+    bulk_load_lock: Mutex<()>,
+    // Prefix extractor length of the default column family.
+    default_prefix_len: Option<usize>,
 }
 
 /// A simple wrapper round a string, used for errors reported from
@@ -158,10 +173,40 @@ pub struct BlockBasedOptions {
 /// ```
 pub struct Options {
     inner: *mut ffi::rocksdb_options_t,
+    // Length of the prefixes taken by the prefix extractor, if one is set.
+    prefix_len: Option<usize>,
+}
+
+/// An environment through which RocksDB accesses the file system and runs
+/// background jobs (flushes and compactions).
+///
+/// `Env::default()` refers to the process-wide default environment, so its settings
+/// affect all databases using it, including ones opened without `Options::set_env`.
+///
+/// # Examples
+///
+/// ```rust
+/// use exonum_rocksdb::{Env, Options};
+///
+/// let mut env = Env::default();
+/// // Compactions run in the low priority pool, flushes in the high priority one.
+/// env.set_background_threads(4);
+/// env.set_high_priority_background_threads(2);
+/// env.lower_thread_pool_io_priority();
+/// env.lower_thread_pool_cpu_priority();
+///
+/// let mut opts = Options::default();
+/// opts.set_env(&env);
+/// ```
+pub struct Env {
+    inner: *mut ffi::rocksdb_env_t,
 }
 
 /// Optionally disable WAL or sync for this write.
 ///
+/// Cloning the options copies the values set through the methods of this type,
+/// which are also what the `Debug` output shows.
+///
 /// # Examples
 ///
 /// Making an unsafe write of a batch:
@@ -191,6 +236,8 @@ pub struct Options {
 /// ```
 pub struct WriteOptions {
     inner: *mut ffi::rocksdb_writeoptions_t,
+    sync: bool,
+    disable_wal: bool,
 }
 
 /// An opaque type used to represent a column family. Returned from some functions, and used
@@ -198,4 +245,14 @@ pub struct WriteOptions {
 #[derive(Copy, Clone)]
 pub struct ColumnFamily {
     inner: *mut ffi::rocksdb_column_family_handle_t,
+    prefix_len: Option<usize>,
+}
+
+/// A name of a column family together with the options it should be opened with.
+/// Used with `DB::open_cf_descriptors`.
+pub struct ColumnFamilyDescriptor {
+    /// Name of the column family.
+    pub name: String,
+    /// Options of the column family.
+    pub options: Options,
 }
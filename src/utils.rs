@@ -12,16 +12,53 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use ffi::{rocksdb_list_column_families, rocksdb_list_column_families_destroy};
+use ffi::{
+    rocksdb_get_options_from_string, rocksdb_list_column_families,
+    rocksdb_list_column_families_destroy,
+};
+use ColumnFamilyDescriptor;
 use Error;
 use Options;
+use DB;
 
 use libc::size_t;
 
 use std::ffi::{CStr, CString};
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::slice;
 
+/// Column family options which refer to user-supplied objects and therefore
+/// cannot be restored from an `OPTIONS` file.
+const UNSUPPORTED_CF_OPTIONS: &[&str] = &[
+    "comparator",
+    "merge_operator",
+    "compaction_filter",
+    "compaction_filter_factory",
+    "memtable_factory",
+    "table_factory",
+    "prefix_extractor",
+    "memtable_insert_with_hint_prefix_extractor",
+];
+
+/// Block-based table options which cannot be restored from an `OPTIONS` file.
+const UNSUPPORTED_TABLE_OPTIONS: &[&str] = &[
+    "filter_policy",
+    "flush_block_policy_factory",
+    "block_cache",
+    "block_cache_compressed",
+    "persistent_cache",
+];
+
+/// Summary of a database produced by `health_check`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealthReport {
+    /// Names of the checked column families.
+    pub column_families: Vec<String>,
+    /// Estimated total number of keys across the checked column families.
+    pub estimated_num_keys: u64,
+}
+
 pub fn to_cpath<P: AsRef<Path>>(path: P) -> Result<CString, Error> {
     match CString::new(path.as_ref().to_string_lossy().as_bytes()) {
         Ok(c) => Ok(c),
@@ -53,3 +90,244 @@ pub fn get_cf_names<P: AsRef<Path>>(path: P) -> Result<Vec<String>, Error> {
 
     Ok(result)
 }
+
+/// Lists column families of the database at `path` together with the options
+/// they were last opened with, as persisted in the latest `OPTIONS` file.
+///
+/// The result can be passed to `DB::open_cf_descriptors` as is. Options referring
+/// to user-supplied objects (comparators, merge operators, compaction filters,
+/// prefix extractors, filter policies and caches) are not persisted in a restorable
+/// form and are left at their defaults; set them on the returned options before
+/// opening the database if the column families use them.
+pub fn get_cf_descriptors<P: AsRef<Path>>(path: P) -> Result<Vec<ColumnFamilyDescriptor>, Error> {
+    let contents = read_latest_options(path.as_ref())?;
+    let mut cfs: Vec<(String, Vec<String>)> = Vec::new();
+    for (section, options) in parse_options_file(&contents) {
+        if let Some(name) = section_cf_name(&section, "CFOptions") {
+            let options = filter_options(&options, UNSUPPORTED_CF_OPTIONS);
+            cfs.push((name.to_owned(), options));
+        } else if let Some(name) = section_cf_name(&section, "TableOptions/BlockBasedTable") {
+            let table_options = filter_options(&options, UNSUPPORTED_TABLE_OPTIONS);
+            if let Some(&mut (_, ref mut options)) = cfs.iter_mut().find(|cf| cf.0 == name) {
+                options.push(format!(
+                    "block_based_table_factory={{{}}}",
+                    table_options.join(";")
+                ));
+            }
+        }
+    }
+
+    cfs.into_iter()
+        .map(|(name, options)| {
+            let options = options_from_string(&options.join(";"))?;
+            Ok(ColumnFamilyDescriptor::new(name, options))
+        })
+        .collect()
+}
+
+/// Reads the value of a column family option of the database at `path`
+/// from the latest `OPTIONS` file.
+///
+/// Returns `None` if the column family or the option is not present in the file.
+/// RocksDB rewrites the file whenever options are changed on an open database,
+/// so the result reflects such changes as well.
+pub(crate) fn get_cf_option(
+    path: &Path,
+    cf_name: &str,
+    option: &str,
+) -> Result<Option<String>, Error> {
+    let contents = read_latest_options(path)?;
+    let value = parse_options_file(&contents)
+        .into_iter()
+        .find(|&(ref section, _)| section_cf_name(section, "CFOptions") == Some(cf_name))
+        .and_then(|(_, options)| {
+            options
+                .into_iter()
+                .find(|&(ref key, _)| key == option)
+                .map(|(_, value)| value)
+        });
+    Ok(value)
+}
+
+/// Reads the contents of the latest `OPTIONS` file in the database directory.
+fn read_latest_options(path: &Path) -> Result<String, Error> {
+    let options_file = match latest_options_file(path)? {
+        Some(options_file) => options_file,
+        None => return Err(Error::new(format!("No options file found in {:?}.", path))),
+    };
+    fs::read_to_string(&options_file).map_err(|e| {
+        Error::new(format!(
+            "Failed to read options file {:?}: `{:?}`.",
+            options_file, e
+        ))
+    })
+}
+
+/// Finds the `OPTIONS-<number>` file with the largest number in the database directory.
+fn latest_options_file(path: &Path) -> Result<Option<PathBuf>, Error> {
+    let entries = fs::read_dir(path)
+        .map_err(|e| Error::new(format!("Failed to read RocksDB directory: `{:?}`.", e)))?;
+
+    let mut latest: Option<(u64, PathBuf)> = None;
+    for entry in entries {
+        let entry = entry
+            .map_err(|e| Error::new(format!("Failed to read RocksDB directory: `{:?}`.", e)))?;
+        let number = entry.file_name().to_str().and_then(|name| {
+            if name.starts_with("OPTIONS-") {
+                name["OPTIONS-".len()..].parse::<u64>().ok()
+            } else {
+                None
+            }
+        });
+        if let Some(number) = number {
+            if latest.as_ref().map_or(true, |&(n, _)| number > n) {
+                latest = Some((number, entry.path()));
+            }
+        }
+    }
+
+    Ok(latest.map(|(_, path)| path))
+}
+
+/// Splits an `OPTIONS` file into sections with their `key=value` pairs.
+fn parse_options_file(contents: &str) -> Vec<(String, Vec<(String, String)>)> {
+    let mut sections: Vec<(String, Vec<(String, String)>)> = Vec::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            sections.push((line[1..line.len() - 1].to_owned(), Vec::new()));
+        } else if let Some(pos) = line.find('=') {
+            if let Some(&mut (_, ref mut options)) = sections.last_mut() {
+                let key = line[..pos].trim().to_owned();
+                let value = line[pos + 1..].trim().to_owned();
+                options.push((key, value));
+            }
+        }
+    }
+    sections
+}
+
+/// Returns the column family name of a section like `CFOptions "name"`.
+fn section_cf_name<'a>(section: &'a str, kind: &str) -> Option<&'a str> {
+    if !section.starts_with(kind) {
+        return None;
+    }
+    let name = section[kind.len()..].trim();
+    if name.len() >= 2 && name.starts_with('"') && name.ends_with('"') {
+        Some(&name[1..name.len() - 1])
+    } else {
+        None
+    }
+}
+
+fn filter_options(options: &[(String, String)], unsupported: &[&str]) -> Vec<String> {
+    options
+        .iter()
+        .filter(|&&(ref key, _)| !unsupported.contains(&key.as_str()))
+        .map(|&(ref key, ref value)| format!("{}={}", key, value))
+        .collect()
+}
+
+fn options_from_string(opts_str: &str) -> Result<Options, Error> {
+    let base = Options::default();
+    let options = Options::default();
+    let c_opts = CString::new(opts_str)
+        .map_err(|_| Error::new("Options string contains a null byte.".to_owned()))?;
+    unsafe {
+        ffi_try!(rocksdb_get_options_from_string(
+            base.inner,
+            c_opts.as_ptr(),
+            options.inner
+        ));
+    }
+    Ok(options)
+}
+
+/// Checks whether a database exists at `path` without opening it.
+///
+/// A database is considered to exist if its `CURRENT` file refers to a manifest
+/// which is present in the database directory.
+pub fn database_exists<P: AsRef<Path>>(path: P) -> bool {
+    let path = path.as_ref();
+    let current = match fs::read_to_string(path.join("CURRENT")) {
+        Ok(current) => current,
+        Err(_) => return false,
+    };
+    let manifest = current.trim();
+    manifest.starts_with("MANIFEST-") && path.join(manifest).is_file()
+}
+
+/// Checks that the database at `path` can be opened and read.
+///
+/// The database is opened read-only with `opts`, so the check may be run while another
+/// process is using it. Opening replays the manifest and the write-ahead log; after that
+/// the first and the last key of every column family are read, which verifies
+/// the checksums of the blocks at both ends of the key space.
+///
+/// Column families are opened with the options persisted in the latest `OPTIONS` file,
+/// see `get_cf_descriptors`, or with default options if there is no such file.
+/// If they use a custom comparator or a merge operator, which cannot be restored from
+/// that file, use `health_check_cf_descriptors` instead.
+pub fn health_check<P: AsRef<Path>>(opts: &Options, path: P) -> Result<HealthReport, Error> {
+    let path = path.as_ref();
+    if !database_exists(path) {
+        return Err(Error::new(format!("No database found at {:?}.", path)));
+    }
+
+    let cfs = if latest_options_file(path)?.is_some() {
+        get_cf_descriptors(path)?
+    } else {
+        get_cf_names(path)?
+            .into_iter()
+            .map(|name| ColumnFamilyDescriptor::new(name, Options::default()))
+            .collect()
+    };
+    health_check_cf_descriptors(opts, path, cfs)
+}
+
+/// Same as `health_check`, but opens the column families listed in `cfs` with their
+/// own options. Only these column families and the default one are checked; the default
+/// column family is opened with default options unless it is listed in `cfs`.
+pub fn health_check_cf_descriptors<P: AsRef<Path>>(
+    opts: &Options,
+    path: P,
+    mut cfs: Vec<ColumnFamilyDescriptor>,
+) -> Result<HealthReport, Error> {
+    let path = path.as_ref();
+    if !database_exists(path) {
+        return Err(Error::new(format!("No database found at {:?}.", path)));
+    }
+
+    if !cfs.iter().any(|cf| cf.name == "default") {
+        cfs.insert(
+            0,
+            ColumnFamilyDescriptor::new("default", Options::default()),
+        );
+    }
+    let column_families: Vec<String> = cfs.iter().map(|cf| cf.name.clone()).collect();
+    let db = DB::open_cf_descriptors_for_read_only(opts, path, cfs, false)?;
+
+    let mut estimated_num_keys = 0;
+    for name in &column_families {
+        let cf = match db.cf_handle(name) {
+            Some(cf) => cf,
+            None => return Err(Error::new(format!("Invalid column family: {}", name))),
+        };
+
+        let mut iter = db.raw_iterator_cf(cf)?;
+        iter.seek_to_first();
+        iter.status()?;
+        iter.seek_to_last();
+        iter.status()?;
+
+        estimated_num_keys += db.estimate_num_keys_cf(cf)?;
+    }
+
+    Ok(HealthReport {
+        column_families,
+        estimated_num_keys,
+    })
+}
@@ -0,0 +1,443 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Change feed of the keys in the default column family, read from the write-ahead log.
+//!
+//! The WAL files have to be kept around for the changes to be readable, so the database
+//! should be opened with `Options::set_wal_ttl_seconds` or `Options::set_wal_size_limit_mb`.
+//! If the changes a watcher has not read yet are deleted from the WAL, reading fails with
+//! `WatchError::Gap`. Writes made with the WAL disabled are not logged, so they are not
+//! reported either; the gap they leave in the sequence numbers is reported the same way.
+//! The watcher can then be resumed after the gap with `PrefixWatcher::resume_from`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! extern crate tempdir;
+//! extern crate exonum_rocksdb;
+//!
+//! use exonum_rocksdb::watch::ChangeOp;
+//! use exonum_rocksdb::{Options, DB};
+//! use std::time::Duration;
+//! use tempdir::TempDir;
+//!
+//! # fn main() {
+//! let temp_dir = TempDir::new("storage_watch").unwrap();
+//! let mut opts = Options::default();
+//! opts.create_if_missing(true);
+//! opts.set_wal_ttl_seconds(3600);
+//! let db = DB::open(&opts, temp_dir.path()).unwrap();
+//!
+//! let mut watcher = db.watch_prefix(b"user:");
+//! db.put(b"user:1", b"alice").unwrap();
+//! db.put(b"zone:1", b"eu").unwrap();
+//! db.delete(b"user:1").unwrap();
+//!
+//! let event = watcher.recv().unwrap();
+//! assert_eq!(event.key, b"user:1");
+//! assert_eq!(event.op, ChangeOp::Put);
+//! let event = watcher.recv_timeout(Duration::from_secs(1)).unwrap().unwrap();
+//! assert_eq!(event.op, ChangeOp::Delete);
+//! # }
+//! ```
+
+use ffi;
+use {Error, DB};
+
+use std::cmp;
+use std::collections::VecDeque;
+use std::error;
+use std::fmt;
+use std::ptr;
+use std::slice;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Kind of a change of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeOp {
+    /// The value of the key has been set.
+    Put,
+    /// A merge operand has been added for the key.
+    Merge,
+    /// The key has been deleted.
+    Delete,
+}
+
+/// A change of a key committed to the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeEvent {
+    /// Sequence number of the change.
+    pub sequence: u64,
+    /// The changed key.
+    pub key: Vec<u8>,
+    /// Kind of the change.
+    pub op: ChangeOp,
+    /// The new value for `Put`, the merge operand for `Merge`, and `None` for `Delete`.
+    pub value: Option<Vec<u8>>,
+}
+
+/// Error returned by a `PrefixWatcher`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchError {
+    /// The changes starting from `next_sequence` cannot be read from the WAL, because
+    /// the WAL files containing them have been deleted or the changes have been written
+    /// with the WAL disabled. The first change available after the gap has the sequence
+    /// number `resume_from`; call `PrefixWatcher::resume_from` to skip the gap.
+    Gap {
+        /// Sequence number of the first missing change.
+        next_sequence: u64,
+        /// Sequence number the watcher can resume from.
+        resume_from: u64,
+    },
+    /// Reading the WAL has failed.
+    Db(Error),
+}
+
+impl From<Error> for WatchError {
+    fn from(e: Error) -> WatchError {
+        WatchError::Db(e)
+    }
+}
+
+impl error::Error for WatchError {
+    fn description(&self) -> &str {
+        match *self {
+            WatchError::Gap { .. } => "Changes are missing from the WAL",
+            WatchError::Db(ref e) => e.as_ref(),
+        }
+    }
+}
+
+impl fmt::Display for WatchError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            WatchError::Gap {
+                next_sequence,
+                resume_from,
+            } => write!(
+                formatter,
+                "Changes starting from sequence number {} are missing from the WAL, \
+                 the next available change has sequence number {}.",
+                next_sequence, resume_from
+            ),
+            WatchError::Db(ref e) => e.fmt(formatter),
+        }
+    }
+}
+
+/// Interval between the reads of the WAL while waiting for changes, in milliseconds.
+const POLL_INTERVAL_MS: u64 = 10;
+
+/// Stream of the changes of the keys starting with a prefix, committed after
+/// the watcher has been created. Returned by `DB::watch_prefix`.
+///
+/// The changes are read either in batches with `poll`, or one by one with `recv`,
+/// `recv_timeout` or by iterating over the watcher; the latter block until a change
+/// is committed. Range deletions are not reported.
+pub struct PrefixWatcher<'a> {
+    db: &'a DB,
+    prefix: Vec<u8>,
+    next_sequence: u64,
+    pending: VecDeque<ChangeEvent>,
+}
+
+impl<'a> PrefixWatcher<'a> {
+    /// Creates a watcher of the changes committed to `db` from now on.
+    pub fn new(db: &'a DB, prefix: &[u8]) -> PrefixWatcher<'a> {
+        let latest = unsafe { ffi::rocksdb_get_latest_sequence_number(db.inner) };
+        PrefixWatcher {
+            db,
+            prefix: prefix.to_vec(),
+            next_sequence: latest + 1,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Returns the sequence number of the first change which has not been read from
+    /// the WAL yet.
+    pub fn next_sequence(&self) -> u64 {
+        self.next_sequence
+    }
+
+    /// Continues reading from the change with the sequence number `sequence`, skipping
+    /// the changes in between. Changes already read but not returned yet are dropped.
+    pub fn resume_from(&mut self, sequence: u64) {
+        self.next_sequence = sequence;
+        self.pending.clear();
+    }
+
+    /// Returns the changes committed since the previous call, in the commit order,
+    /// without blocking.
+    pub fn poll(&mut self) -> Result<Vec<ChangeEvent>, WatchError> {
+        let result = self.read_wal();
+        // The changes read before an error are returned first; the error is detected
+        // again by the next call.
+        if self.pending.is_empty() {
+            result?;
+        }
+        Ok(self.pending.drain(..).collect())
+    }
+
+    /// Waits for the next change.
+    pub fn recv(&mut self) -> Result<ChangeEvent, WatchError> {
+        loop {
+            if let Some(event) = self.recv_timeout(Duration::from_millis(POLL_INTERVAL_MS))? {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Waits for the next change for at most `timeout`. Returns `None` if no change
+    /// has been committed in time.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<ChangeEvent>, WatchError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(event));
+            }
+            self.read_wal()?;
+            if !self.pending.is_empty() {
+                continue;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            thread::sleep(cmp::min(
+                Duration::from_millis(POLL_INTERVAL_MS),
+                deadline - now,
+            ));
+        }
+    }
+
+    /// Reads the changes committed since `next_sequence` into `pending`.
+    fn read_wal(&mut self) -> Result<(), WatchError> {
+        let latest = unsafe { ffi::rocksdb_get_latest_sequence_number(self.db.inner) };
+        while self.next_sequence <= latest {
+            let start = self.next_sequence;
+            let iter = WalIterator::new(self.db, self.next_sequence)?;
+            while iter.valid() && self.next_sequence <= latest {
+                let (sequence, data) = iter.batch();
+                if sequence > self.next_sequence {
+                    return Err(WatchError::Gap {
+                        next_sequence: self.next_sequence,
+                        resume_from: sequence,
+                    });
+                }
+                let next_sequence = self.next_sequence;
+                let prefix = &self.prefix;
+                let pending = &mut self.pending;
+                let end = parse_batch(&data, sequence, |event| {
+                    if event.sequence >= next_sequence && event.key.starts_with(prefix) {
+                        pending.push_back(event);
+                    }
+                })?;
+                self.next_sequence = self.next_sequence.max(end);
+                iter.next();
+            }
+
+            // The iterator stops at a gap in the sequence numbers; a new one is
+            // positioned past the gap, if there are changes after it.
+            if self.next_sequence == start {
+                iter.status()?;
+                return Err(WatchError::Gap {
+                    next_sequence: self.next_sequence,
+                    resume_from: latest + 1,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Iterator for PrefixWatcher<'a> {
+    type Item = Result<ChangeEvent, WatchError>;
+
+    /// Waits for the next change. Never returns `None`.
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.recv())
+    }
+}
+
+struct WalIterator {
+    inner: *mut ffi::rocksdb_wal_iterator_t,
+}
+
+impl WalIterator {
+    fn new(db: &DB, since: u64) -> Result<WalIterator, Error> {
+        let inner =
+            unsafe { ffi_try!(ffi::rocksdb_get_updates_since(db.inner, since, ptr::null())) };
+        Ok(WalIterator { inner })
+    }
+
+    fn valid(&self) -> bool {
+        unsafe { ffi::rocksdb_wal_iter_valid(self.inner) != 0 }
+    }
+
+    fn next(&self) {
+        unsafe {
+            ffi::rocksdb_wal_iter_next(self.inner);
+        }
+    }
+
+    fn status(&self) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_wal_iter_status(self.inner));
+        }
+        Ok(())
+    }
+
+    /// Returns the sequence number of the current batch and its serialized contents.
+    fn batch(&self) -> (u64, Vec<u8>) {
+        unsafe {
+            let mut sequence = 0;
+            let batch = ffi::rocksdb_wal_iter_get_batch(self.inner, &mut sequence);
+            let mut size = 0;
+            let data = ffi::rocksdb_writebatch_data(batch, &mut size);
+            let data = slice::from_raw_parts(data as *const u8, size).to_vec();
+            ffi::rocksdb_writebatch_destroy(batch);
+            (sequence, data)
+        }
+    }
+}
+
+impl Drop for WalIterator {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_wal_iter_destroy(self.inner);
+        }
+    }
+}
+
+// Record types of the serialized write batch, see `db/dbformat.h` in RocksDB.
+const TYPE_DELETION: u8 = 0x0;
+const TYPE_VALUE: u8 = 0x1;
+const TYPE_MERGE: u8 = 0x2;
+const TYPE_LOG_DATA: u8 = 0x3;
+const TYPE_CF_DELETION: u8 = 0x4;
+const TYPE_CF_VALUE: u8 = 0x5;
+const TYPE_CF_MERGE: u8 = 0x6;
+const TYPE_SINGLE_DELETION: u8 = 0x7;
+const TYPE_CF_SINGLE_DELETION: u8 = 0x8;
+const TYPE_BEGIN_PREPARE_XID: u8 = 0x9;
+const TYPE_END_PREPARE_XID: u8 = 0xA;
+const TYPE_COMMIT_XID: u8 = 0xB;
+const TYPE_ROLLBACK_XID: u8 = 0xC;
+const TYPE_NOOP: u8 = 0xD;
+const TYPE_CF_RANGE_DELETION: u8 = 0xE;
+const TYPE_RANGE_DELETION: u8 = 0xF;
+const TYPE_CF_BLOB_INDEX: u8 = 0x10;
+const TYPE_BLOB_INDEX: u8 = 0x11;
+const TYPE_BEGIN_PERSISTED_PREPARE_XID: u8 = 0x12;
+const TYPE_BEGIN_UNPREPARE_XID: u8 = 0x13;
+
+/// Size of the batch header: the sequence number followed by the record count.
+const BATCH_HEADER_SIZE: usize = 12;
+
+/// Calls `f` for every change of the default column family in the serialized write
+/// batch starting at `sequence`. Returns the sequence number following the batch.
+fn parse_batch<F>(data: &[u8], mut sequence: u64, mut f: F) -> Result<u64, Error>
+where
+    F: FnMut(ChangeEvent),
+{
+    if data.len() < BATCH_HEADER_SIZE {
+        return Err(malformed_batch());
+    }
+    let mut reader = Reader {
+        data: &data[BATCH_HEADER_SIZE..],
+    };
+
+    while let Some(tag) = reader.byte() {
+        let (cf, op) = match tag {
+            TYPE_VALUE => (0, Some(ChangeOp::Put)),
+            TYPE_MERGE => (0, Some(ChangeOp::Merge)),
+            TYPE_DELETION | TYPE_SINGLE_DELETION => (0, Some(ChangeOp::Delete)),
+            TYPE_CF_VALUE => (reader.varint32()?, Some(ChangeOp::Put)),
+            TYPE_CF_MERGE => (reader.varint32()?, Some(ChangeOp::Merge)),
+            TYPE_CF_DELETION | TYPE_CF_SINGLE_DELETION => {
+                (reader.varint32()?, Some(ChangeOp::Delete))
+            }
+            TYPE_RANGE_DELETION | TYPE_BLOB_INDEX => (0, None),
+            TYPE_CF_RANGE_DELETION | TYPE_CF_BLOB_INDEX => (reader.varint32()?, None),
+            TYPE_LOG_DATA | TYPE_END_PREPARE_XID | TYPE_COMMIT_XID | TYPE_ROLLBACK_XID => {
+                // Markers carrying a blob or a transaction ID don't use a sequence number.
+                reader.slice()?;
+                continue;
+            }
+            TYPE_BEGIN_PREPARE_XID
+            | TYPE_BEGIN_PERSISTED_PREPARE_XID
+            | TYPE_BEGIN_UNPREPARE_XID
+            | TYPE_NOOP => continue,
+            _ => return Err(malformed_batch()),
+        };
+
+        let key = reader.slice()?;
+        let value = match op {
+            Some(ChangeOp::Delete) => None,
+            // Range deletions and blob indexes have a second slice as well.
+            _ => Some(reader.slice()?),
+        };
+        if let Some(op) = op {
+            if cf == 0 {
+                f(ChangeEvent {
+                    sequence,
+                    key: key.to_vec(),
+                    op,
+                    value: value.map(|value| value.to_vec()),
+                });
+            }
+        }
+        sequence += 1;
+    }
+    Ok(sequence)
+}
+
+fn malformed_batch() -> Error {
+    Error::new("Malformed write batch in the WAL.".to_owned())
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Option<u8> {
+        let (&byte, rest) = self.data.split_first()?;
+        self.data = rest;
+        Some(byte)
+    }
+
+    fn varint32(&mut self) -> Result<u32, Error> {
+        let mut result = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.byte().ok_or_else(malformed_batch)?;
+            result |= u32::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+        Err(malformed_batch())
+    }
+
+    fn slice(&mut self) -> Result<&'a [u8], Error> {
+        let len = self.varint32()? as usize;
+        if self.data.len() < len {
+            return Err(malformed_batch());
+        }
+        let (slice, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(slice)
+    }
+}
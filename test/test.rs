@@ -14,10 +14,22 @@
 //
 
 extern crate exonum_rocksdb;
+#[cfg(feature = "metrics")]
+extern crate metrics_util;
 extern crate tempdir;
+#[cfg(feature = "tracing")]
+extern crate tracing;
 
+mod test_bulk_load;
 mod test_column_family;
+mod test_disk_usage;
+mod test_instrument;
 mod test_iterator;
 mod test_multithreaded;
+mod test_prefix_iterator;
+mod test_property;
 mod test_raw_iterator;
 mod test_rocksdb_options;
+mod test_update;
+mod test_utils;
+mod test_watch;
@@ -0,0 +1,193 @@
+// Copyright 2014 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use exonum_rocksdb::bulk_load::BulkLoader;
+use exonum_rocksdb::{IteratorMode, Options, DB};
+use tempdir::TempDir;
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+
+fn entries(n: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
+    (0..n)
+        .map(|i| {
+            (
+                format!("key{:05}", i).into_bytes(),
+                format!("value{}", i).into_bytes(),
+            )
+        })
+        .collect()
+}
+
+/// Reads the `disable_auto_compactions` setting of the default column family
+/// from the latest `OPTIONS` file.
+fn auto_compactions_disabled(path: &Path) -> bool {
+    let options_file = fs::read_dir(path)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter_map(|path| {
+            let number = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .filter(|name| name.starts_with("OPTIONS-"))
+                .and_then(|name| name["OPTIONS-".len()..].parse::<u64>().ok());
+            number.map(|number| (number, path))
+        })
+        .max()
+        .unwrap()
+        .1;
+    let contents = fs::read_to_string(options_file).unwrap();
+    contents
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| *line != "[CFOptions \"default\"]")
+        .find(|line| line.starts_with("disable_auto_compactions="))
+        .unwrap()
+        == "disable_auto_compactions=true"
+}
+
+#[test]
+pub fn test_bulk_load() {
+    let temp_dir = TempDir::new("_rust_rocksdb_bulk_load").unwrap();
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    let db = DB::open(&opts, temp_dir.path()).unwrap();
+
+    let mut loader = BulkLoader::new(&db, &opts);
+    // Make sure the data is spread over several files written in parallel.
+    loader.set_entries_per_file(100);
+    loader.set_max_threads(2);
+    loader.load(entries(1050)).unwrap();
+
+    let loaded: Vec<_> = db
+        .iterator(IteratorMode::Start)
+        .map(|(k, v)| (k.to_vec(), v.to_vec()))
+        .collect();
+    assert_eq!(loaded, entries(1050));
+
+    // The database keeps accepting regular writes afterwards.
+    db.put(b"key99999", b"value").unwrap();
+    assert!(db.get(b"key99999").unwrap().is_some());
+}
+
+#[test]
+pub fn test_bulk_load_cf() {
+    let temp_dir = TempDir::new("_rust_rocksdb_bulk_load_cf").unwrap();
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    let db = DB::open(&opts, temp_dir.path()).unwrap();
+    let cf1 = db.create_cf("cf1", &opts).unwrap();
+
+    BulkLoader::new_cf(&db, cf1, &opts)
+        .load(entries(10))
+        .unwrap();
+    assert_eq!(&*db.get_cf(cf1, b"key00003").unwrap().unwrap(), b"value3");
+    assert!(db.get(b"key00003").unwrap().is_none());
+}
+
+#[test]
+pub fn test_bulk_load_unsorted() {
+    let temp_dir = TempDir::new("_rust_rocksdb_bulk_load_unsorted").unwrap();
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    let db = DB::open(&opts, temp_dir.path()).unwrap();
+
+    let mut unsorted = entries(10);
+    unsorted.swap(3, 7);
+    assert!(BulkLoader::new(&db, &opts).load(unsorted).is_err());
+    assert!(db.iterator(IteratorMode::Start).next().is_none());
+
+    // An empty stream is a no-op.
+    BulkLoader::new(&db, &opts)
+        .load(Vec::<(Vec<u8>, Vec<u8>)>::new())
+        .unwrap();
+}
+
+#[test]
+pub fn test_bulk_load_unsorted_across_files() {
+    let temp_dir = TempDir::new("_rust_rocksdb_bulk_load_unsorted_across_files").unwrap();
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    let db = DB::open(&opts, temp_dir.path()).unwrap();
+
+    // Each file is sorted by itself, but the second one starts before the first one ends.
+    let all = entries(4);
+    let unsorted = vec![
+        all[2].clone(),
+        all[3].clone(),
+        all[0].clone(),
+        all[1].clone(),
+    ];
+    let mut loader = BulkLoader::new(&db, &opts);
+    loader.set_entries_per_file(2);
+    assert!(loader.load(unsorted).is_err());
+    assert!(db.iterator(IteratorMode::Start).next().is_none());
+
+    // A key repeated on the boundary is rejected as well.
+    let repeated = vec![
+        all[0].clone(),
+        all[1].clone(),
+        all[1].clone(),
+        all[2].clone(),
+    ];
+    assert!(loader.load(repeated).is_err());
+    assert!(db.iterator(IteratorMode::Start).next().is_none());
+}
+
+#[test]
+pub fn test_bulk_load_keeps_auto_compactions_disabled() {
+    let temp_dir = TempDir::new("_rust_rocksdb_bulk_load_auto_compactions").unwrap();
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_disable_auto_compactions(true);
+    let db = DB::open(&opts, temp_dir.path()).unwrap();
+
+    BulkLoader::new(&db, &opts).load(entries(10)).unwrap();
+    assert!(auto_compactions_disabled(temp_dir.path()));
+}
+
+#[test]
+pub fn test_concurrent_bulk_loads_restore_auto_compactions() {
+    let temp_dir = TempDir::new("_rust_rocksdb_concurrent_bulk_loads").unwrap();
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    let db = Arc::new(DB::open(&opts, temp_dir.path()).unwrap());
+
+    let loads: Vec<_> = (0..4)
+        .map(|i| {
+            let db = Arc::clone(&db);
+            thread::spawn(move || {
+                let opts = Options::default();
+                let entries = (0..1000).map(|j| {
+                    (
+                        format!("load{}_key{:05}", i, j).into_bytes(),
+                        b"value".to_vec(),
+                    )
+                });
+                let mut loader = BulkLoader::new(&db, &opts);
+                loader.set_entries_per_file(100);
+                loader.load(entries).unwrap();
+            })
+        })
+        .collect();
+    for load in loads {
+        load.join().unwrap();
+    }
+
+    assert_eq!(db.iterator(IteratorMode::Start).count(), 4000);
+    assert!(!auto_compactions_disabled(temp_dir.path()));
+}
@@ -14,6 +14,8 @@
 //
 
 use exonum_rocksdb::{MergeOperands, Options, DB};
+use std::sync::Arc;
+use std::thread;
 use tempdir::TempDir;
 
 #[test]
@@ -78,6 +80,29 @@ pub fn test_column_family() {
     assert!(DB::destroy(&Options::default(), path).is_ok());
 }
 
+#[test]
+pub fn test_get_or_create_cf() {
+    let temp_dir = TempDir::new("_rust_rocksdb_get_or_create_cf").unwrap();
+    let db = Arc::new(DB::open_default(temp_dir.path()).unwrap());
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let db = Arc::clone(&db);
+            thread::spawn(move || {
+                db.get_or_create_cf("cf1", &Options::default()).unwrap();
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let cf1 = db.cf_handle("cf1").unwrap();
+    db.put_cf(cf1, b"k1", b"v1").unwrap();
+    let cf1 = db.get_or_create_cf("cf1", &Options::default()).unwrap();
+    assert_eq!(&*db.get_cf(cf1, b"k1").unwrap().unwrap(), b"v1");
+}
+
 #[test]
 #[ignore]
 fn test_merge_operator() {
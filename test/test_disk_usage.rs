@@ -0,0 +1,75 @@
+// Copyright 2014 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use exonum_rocksdb::{Options, DB};
+use std::thread;
+use std::time::Duration;
+use tempdir::TempDir;
+
+#[test]
+pub fn test_disk_usage() {
+    let temp_dir = TempDir::new("_rust_rocksdb_disk_usage").unwrap();
+    let db = DB::open_default(temp_dir.path()).unwrap();
+    let cf1 = db.create_cf("cf1", &Options::default()).unwrap();
+
+    let usage = db.disk_usage().unwrap();
+    assert_eq!(usage.sst_bytes, 0);
+    assert_eq!(usage.column_families.len(), 2);
+    assert!(usage.column_families.contains_key("default"));
+    assert!(usage.column_families.contains_key("cf1"));
+
+    for i in 0..100 {
+        db.put_cf(cf1, format!("key{}", i).as_bytes(), &[0; 100])
+            .unwrap();
+    }
+    let usage = db.disk_usage().unwrap();
+    assert!(usage.wal_bytes > 0);
+
+    // Compaction flushes the memtable into an SST file first.
+    db.compact_range_cf(cf1, None, None);
+    let usage = db.disk_usage().unwrap();
+    let cf1_usage = usage.column_families["cf1"];
+    assert!(cf1_usage.sst_bytes > 0);
+    assert_eq!(usage.column_families["default"].sst_bytes, 0);
+    assert_eq!(usage.sst_bytes, cf1_usage.sst_bytes);
+    assert_eq!(usage.blob_bytes, 0);
+}
+
+#[test]
+pub fn test_disk_usage_archived_wal() {
+    let temp_dir = TempDir::new("_rust_rocksdb_disk_usage_archived_wal").unwrap();
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_wal_ttl_seconds(3600);
+    let db = DB::open(&opts, temp_dir.path()).unwrap();
+    assert_eq!(db.disk_usage().unwrap().archived_wal_bytes, 0);
+
+    for i in 0..100 {
+        db.put(format!("key{}", i).as_bytes(), &[0; 100]).unwrap();
+    }
+    // The flush makes the WAL obsolete, so it is moved to the archive.
+    db.compact_range(None, None);
+
+    // Obsolete files are archived in the background after the flush.
+    let mut archived_wal_bytes = 0;
+    for _ in 0..100 {
+        archived_wal_bytes = db.disk_usage().unwrap().archived_wal_bytes;
+        if archived_wal_bytes > 0 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(archived_wal_bytes > 0);
+}
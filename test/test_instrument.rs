@@ -0,0 +1,171 @@
+// Copyright 2014 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+#[cfg(feature = "tracing")]
+mod tracing_spans {
+    use exonum_rocksdb::{Options, DB};
+    use tempdir::TempDir;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{self, Event, Metadata, Subscriber};
+
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+
+    type Fields = HashMap<String, String>;
+
+    /// Subscriber capturing the fields of all spans.
+    #[derive(Clone, Default)]
+    struct Spans(Arc<Mutex<Vec<Fields>>>);
+
+    struct FieldVisitor<'a>(&'a mut Fields);
+
+    impl<'a> Visit for FieldVisitor<'a> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_owned(), value.to_owned());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0
+                .insert(field.name().to_owned(), format!("{:?}", value));
+        }
+    }
+
+    impl Subscriber for Spans {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes) -> Id {
+            let mut fields = Fields::new();
+            span.record(&mut FieldVisitor(&mut fields));
+            let mut spans = self.0.lock().unwrap();
+            spans.push(fields);
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record) {
+            let mut spans = self.0.lock().unwrap();
+            let fields = &mut spans[span.into_u64() as usize - 1];
+            values.record(&mut FieldVisitor(fields));
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event) {}
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    pub fn test_tracing_spans() {
+        let temp_dir = TempDir::new("_rust_rocksdb_tracing_spans").unwrap();
+        let db = DB::open_default(temp_dir.path()).unwrap();
+        let cf1 = db.create_cf("cf1", &Options::default()).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        let read_only =
+            DB::open_for_read_only(&Options::default(), temp_dir.path(), false).unwrap();
+
+        let spans = Spans::default();
+        tracing::subscriber::with_default(spans.clone(), || {
+            db.put(b"key", b"value").unwrap();
+            db.get_cf(cf1, b"k1").unwrap();
+            let mut iter = db.raw_iterator();
+            iter.seek(b"k1");
+            assert!(read_only.put(b"key", b"value").is_err());
+        });
+
+        let spans = spans.0.lock().unwrap();
+        assert_eq!(spans.len(), 4);
+        for span in spans.iter() {
+            assert!(span["duration_us"].parse::<u64>().is_ok());
+        }
+
+        assert_eq!(spans[0]["op"], "put");
+        assert_eq!(spans[0]["cf"], "default");
+        assert_eq!(spans[0]["key_size"], "3");
+        assert!(!spans[0].contains_key("error"));
+
+        assert_eq!(spans[1]["op"], "get");
+        assert_eq!(spans[1]["cf"], "cf1");
+        assert_eq!(spans[1]["key_size"], "2");
+
+        // Iterators are instrumented per seek.
+        assert_eq!(spans[2]["op"], "seek");
+        assert_eq!(spans[2]["cf"], "default");
+        assert!(!spans[2].contains_key("key_size"));
+
+        assert_eq!(spans[3]["op"], "put");
+        assert!(spans[3].contains_key("error"));
+    }
+}
+
+#[cfg(feature = "metrics")]
+mod metrics_recorder {
+    use exonum_rocksdb::{Options, DB};
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use tempdir::TempDir;
+
+    use std::collections::HashMap;
+
+    #[test]
+    pub fn test_metrics() {
+        // Only the metrics recorded on this thread are captured.
+        let recorder = DebuggingRecorder::per_thread();
+        let snapshotter = recorder.snapshotter();
+        recorder.install().unwrap();
+
+        let temp_dir = TempDir::new("_rust_rocksdb_metrics").unwrap();
+        let db = DB::open_default(temp_dir.path()).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.put(b"k2", b"v2").unwrap();
+        db.get(b"k1").unwrap();
+        let mut iter = db.raw_iterator();
+        iter.seek_to_first();
+        let read_only =
+            DB::open_for_read_only(&Options::default(), temp_dir.path(), false).unwrap();
+        assert!(read_only.put(b"k3", b"v3").is_err());
+
+        let mut latencies = HashMap::new();
+        let mut errors = HashMap::new();
+        for (key, _, _, value) in snapshotter.snapshot().into_vec() {
+            let key = key.key();
+            let op = key
+                .labels()
+                .find(|label| label.key() == "op")
+                .map(|label| label.value().to_owned())
+                .unwrap();
+            match (key.name(), value) {
+                ("rocksdb_operation_duration_seconds", DebugValue::Histogram(values)) => {
+                    latencies.insert(op, values.len());
+                }
+                ("rocksdb_operation_errors_total", DebugValue::Counter(count)) => {
+                    errors.insert(op, count);
+                }
+                (name, value) => panic!("Unexpected metric {}: {:?}", name, value),
+            }
+        }
+
+        // The failed put is timed as well.
+        assert_eq!(latencies["put"], 3);
+        assert_eq!(latencies["get"], 1);
+        assert_eq!(latencies["seek"], 1);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors["put"], 1);
+    }
+}
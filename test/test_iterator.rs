@@ -13,7 +13,8 @@
 // limitations under the License.
 //
 
-use exonum_rocksdb::{Direction, IteratorMode, Options, DB};
+use exonum_rocksdb::{DBIterator, Direction, IteratorMode, Options, DB};
+use tempdir::TempDir;
 
 fn cba(input: &[u8]) -> Box<[u8]> {
     input.to_vec().into_boxed_slice()
@@ -141,13 +142,18 @@ pub fn test_iterator() {
             let iterator4 = db.iterator(IteratorMode::From(b"k5", Direction::Forward));
             assert!(!iterator4.valid());
             let iterator5 = db.iterator(IteratorMode::From(b"k0", Direction::Reverse));
-            assert!(iterator5.valid());
+            assert!(!iterator5.valid());
             let iterator6 = db.iterator(IteratorMode::From(b"k1", Direction::Reverse));
             assert!(iterator6.valid());
             let iterator7 = db.iterator(IteratorMode::From(b"k11", Direction::Reverse));
             assert!(iterator7.valid());
             let iterator8 = db.iterator(IteratorMode::From(b"k5", Direction::Reverse));
-            assert!(!iterator8.valid());
+            assert!(iterator8.valid());
+        }
+        {
+            let iterator1 = db.iterator(IteratorMode::From(b"k25", Direction::Reverse));
+            let expected = vec![(cba(&k2), cba(&v2)), (cba(&k1), cba(&v1))];
+            assert_eq!(iterator1.collect::<Vec<_>>(), expected);
         }
         {
             let mut iterator1 = db.iterator(IteratorMode::From(b"k4", Direction::Forward));
@@ -159,3 +165,68 @@ pub fn test_iterator() {
     }
     assert!(DB::destroy(&Options::default(), path).is_ok());
 }
+
+#[test]
+pub fn test_iterator_repositioning() {
+    let temp_dir = TempDir::new("_rust_rocksdb_iterator_repositioning").unwrap();
+    let db = DB::open_default(temp_dir.path()).unwrap();
+    for key in &[b"a1", b"a2", b"b1", b"b2", b"c1"] {
+        db.put(*key, b"value").unwrap();
+    }
+
+    let keys = |iter: &mut DBIterator, n: usize| -> Vec<Box<[u8]>> {
+        iter.take(n).map(|(key, _)| key).collect()
+    };
+
+    let mut iter = db.iterator(IteratorMode::Start);
+    assert_eq!(keys(&mut iter, 1), vec![cba(b"a1")]);
+
+    // Seeking keeps the forward direction.
+    iter.seek(b"b");
+    assert_eq!(keys(&mut iter, 2), vec![cba(b"b1"), cba(b"b2")]);
+
+    // Switching the direction after partial consumption continues from the last item.
+    iter.set_direction(Direction::Reverse);
+    assert_eq!(keys(&mut iter, 2), vec![cba(b"b1"), cba(b"a2")]);
+
+    // Seeking keeps the reverse direction.
+    iter.seek_for_prev(b"c");
+    assert_eq!(keys(&mut iter, 2), vec![cba(b"b2"), cba(b"b1")]);
+
+    // Seeking in the reverse direction starts at the last key before the target.
+    iter.seek(b"b15");
+    assert_eq!(keys(&mut iter, 2), vec![cba(b"b1"), cba(b"a2")]);
+
+    iter.seek_to_last();
+    assert_eq!(keys(&mut iter, 1), vec![cba(b"c1")]);
+
+    iter.seek_to_first();
+    assert_eq!(keys(&mut iter, 5).len(), 5);
+}
+
+#[test]
+pub fn test_iterator_set_direction_after_exhaustion() {
+    let temp_dir = TempDir::new("_rust_rocksdb_iterator_exhaustion").unwrap();
+    let db = DB::open_default(temp_dir.path()).unwrap();
+    for key in &[b"k1", b"k2", b"k3"] {
+        db.put(*key, b"value").unwrap();
+    }
+
+    let mut iter = db.iterator(IteratorMode::Start);
+    assert_eq!(iter.by_ref().count(), 3);
+    assert!(iter.next().is_none());
+    iter.set_direction(Direction::Reverse);
+    assert_eq!(iter.next().map(|(key, _)| key), Some(cba(b"k3")));
+
+    assert_eq!(iter.by_ref().count(), 2);
+    iter.set_direction(Direction::Forward);
+    assert_eq!(iter.next().map(|(key, _)| key), Some(cba(b"k1")));
+
+    // Keeping the direction of an exhausted iterator doesn't restart it.
+    let mut iter = db.iterator(IteratorMode::From(b"k4", Direction::Forward));
+    assert!(iter.next().is_none());
+    iter.set_direction(Direction::Forward);
+    assert!(iter.next().is_none());
+    iter.set_direction(Direction::Reverse);
+    assert_eq!(iter.next().map(|(key, _)| key), Some(cba(b"k3")));
+}
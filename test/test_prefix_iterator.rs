@@ -0,0 +1,77 @@
+// Copyright 2014 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use exonum_rocksdb::{Options, DB};
+use tempdir::TempDir;
+
+fn keys<I: Iterator<Item = (Box<[u8]>, Box<[u8]>)>>(iter: I) -> Vec<Vec<u8>> {
+    iter.map(|(k, _)| k.to_vec()).collect()
+}
+
+#[test]
+pub fn test_prefix_iterator() {
+    let temp_dir = TempDir::new("_rust_rocksdb_prefix_iterator").unwrap();
+    let db = DB::open_default(temp_dir.path()).unwrap();
+    db.put(b"a1", b"v").unwrap();
+    db.put(b"b1", b"v").unwrap();
+    db.put(b"b2", b"v").unwrap();
+    db.put(b"b\xff", b"v").unwrap();
+    db.put(b"c1", b"v").unwrap();
+
+    assert_eq!(
+        keys(db.prefix_iterator(b"b")),
+        vec![b"b1".to_vec(), b"b2".to_vec(), b"b\xff".to_vec()]
+    );
+    assert_eq!(keys(db.prefix_iterator(b"b2")), vec![b"b2".to_vec()]);
+    assert!(keys(db.prefix_iterator(b"d")).is_empty());
+    assert_eq!(keys(db.prefix_iterator(b"")).len(), 5);
+}
+
+#[test]
+pub fn test_prefix_iterator_cf() {
+    let temp_dir = TempDir::new("_rust_rocksdb_prefix_iterator_cf").unwrap();
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_fixed_prefix_extractor(2);
+    let db = DB::open(&opts, temp_dir.path()).unwrap();
+    let cf1 = db.create_cf("cf1", &opts).unwrap();
+    db.put_cf(cf1, b"aa1", b"v").unwrap();
+    db.put_cf(cf1, b"aa2", b"v").unwrap();
+    db.put_cf(cf1, b"ab1", b"v").unwrap();
+    db.put(b"aa3", b"v").unwrap();
+
+    let iter = db.prefix_iterator_cf(cf1, b"aa").unwrap();
+    assert_eq!(keys(iter), vec![b"aa1".to_vec(), b"aa2".to_vec()]);
+}
+
+#[test]
+pub fn test_prefix_iterator_shorter_than_extracted_prefix() {
+    let temp_dir = TempDir::new("_rust_rocksdb_prefix_iterator_short_prefix").unwrap();
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_fixed_prefix_extractor(2);
+    let db = DB::open(&opts, temp_dir.path()).unwrap();
+    let cf1 = db.create_cf("cf1", &opts).unwrap();
+    for &key in &[&b"aa1"[..], b"ab1", b"ac1", b"b1"] {
+        db.put(key, b"v").unwrap();
+        db.put_cf(cf1, key, b"v").unwrap();
+    }
+    db.compact_range(None, None);
+
+    // The prefix spans several extracted prefixes.
+    let expected = vec![b"aa1".to_vec(), b"ab1".to_vec(), b"ac1".to_vec()];
+    assert_eq!(keys(db.prefix_iterator(b"a")), expected);
+    assert_eq!(keys(db.prefix_iterator_cf(cf1, b"a").unwrap()), expected);
+}
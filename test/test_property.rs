@@ -0,0 +1,75 @@
+// Copyright 2014 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use exonum_rocksdb::{Options, DB};
+use tempdir::TempDir;
+
+#[test]
+pub fn test_is_empty() {
+    let temp_dir = TempDir::new("_rust_rocksdb_is_empty").unwrap();
+    let db = DB::open_default(temp_dir.path()).unwrap();
+    let cf1 = db.create_cf("cf1", &Options::default()).unwrap();
+    assert!(db.is_empty().unwrap());
+    assert!(db.is_empty_cf(cf1).unwrap());
+
+    // A key in any column family makes the database non-empty.
+    db.put_cf(cf1, b"k1", b"v1").unwrap();
+    assert!(!db.is_empty().unwrap());
+    assert!(!db.is_empty_cf(cf1).unwrap());
+    db.delete_cf(cf1, b"k1").unwrap();
+    assert!(db.is_empty().unwrap());
+
+    db.put(b"k1", b"v1").unwrap();
+    assert!(!db.is_empty().unwrap());
+    assert!(db.is_empty_cf(cf1).unwrap());
+    db.delete(b"k1").unwrap();
+    assert!(db.is_empty().unwrap());
+}
+
+#[test]
+pub fn test_estimate_num_keys() {
+    let temp_dir = TempDir::new("_rust_rocksdb_estimate_num_keys").unwrap();
+    let db = DB::open_default(temp_dir.path()).unwrap();
+    let cf1 = db.create_cf("cf1", &Options::default()).unwrap();
+    assert_eq!(db.estimate_num_keys().unwrap(), 0);
+
+    for i in 0..10u8 {
+        db.put(&[i], b"value").unwrap();
+    }
+    db.put_cf(cf1, b"k1", b"v1").unwrap();
+    assert_eq!(db.estimate_num_keys().unwrap(), 11);
+    assert_eq!(db.estimate_num_keys_cf(cf1).unwrap(), 1);
+    assert_eq!(
+        db.property_int_value("rocksdb.estimate-num-keys").unwrap(),
+        Some(10)
+    );
+}
+
+#[test]
+pub fn test_property_value() {
+    let temp_dir = TempDir::new("_rust_rocksdb_property_value").unwrap();
+    let db = DB::open_default(temp_dir.path()).unwrap();
+    assert!(db.property_value("rocksdb.stats").unwrap().is_some());
+    assert!(db
+        .property_value("rocksdb.no-such-property")
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        db.property_int_value("rocksdb.num-immutable-mem-table")
+            .unwrap(),
+        Some(0)
+    );
+    assert!(db.property_int_value("rocksdb.stats").is_err());
+}
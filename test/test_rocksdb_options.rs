@@ -13,7 +13,7 @@
 // limitations under the License.
 //
 
-use exonum_rocksdb::{Options, DB};
+use exonum_rocksdb::{Env, Options, ReadOptions, WriteOptions, DB};
 use tempdir::TempDir;
 
 #[test]
@@ -25,3 +25,99 @@ fn test_set_num_levels() {
     let db = DB::open(&opts, temp_dir.path()).unwrap();
     drop(db);
 }
+
+#[test]
+fn test_manifest_and_file_buffer_options() {
+    let temp_dir = TempDir::new("_rust_rocksdb_test_manifest_options").unwrap();
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_max_manifest_file_size(64 * 1024);
+    opts.set_writable_file_max_buffer_size(64 * 1024);
+    opts.set_skip_stats_update_on_db_open(true);
+    {
+        let db = DB::open(&opts, temp_dir.path()).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+    }
+    let db = DB::open(&opts, temp_dir.path()).unwrap();
+    assert_eq!(&*db.get(b"k1").unwrap().unwrap(), b"v1");
+}
+
+#[test]
+fn test_set_use_fsync() {
+    let temp_dir = TempDir::new("_rust_rocksdb_test_set_use_fsync").unwrap();
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_use_fsync(true);
+    let db = DB::open(&opts, temp_dir.path()).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    assert_eq!(&*db.get(b"k1").unwrap().unwrap(), b"v1");
+}
+
+#[test]
+fn test_avoid_flush_options() {
+    let temp_dir = TempDir::new("_rust_rocksdb_test_avoid_flush").unwrap();
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_avoid_flush_during_shutdown(true);
+    opts.set_avoid_flush_during_recovery(true);
+    {
+        let db = DB::open(&opts, temp_dir.path()).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+    }
+    // The write is recovered from the WAL.
+    let db = DB::open(&opts, temp_dir.path()).unwrap();
+    assert_eq!(&*db.get(b"k1").unwrap().unwrap(), b"v1");
+}
+
+#[test]
+fn test_set_env() {
+    let temp_dir = TempDir::new("_rust_rocksdb_test_set_env").unwrap();
+    let mut env = Env::default();
+    env.set_background_threads(2);
+    env.set_high_priority_background_threads(1);
+    env.lower_thread_pool_io_priority();
+    env.lower_thread_pool_cpu_priority();
+
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_env(&env);
+    let db = DB::open(&opts, temp_dir.path()).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    db.compact_range(None, None);
+    assert_eq!(&*db.get(b"k1").unwrap().unwrap(), b"v1");
+}
+
+#[test]
+fn test_clone_and_debug_read_write_options() {
+    let mut write_opts = WriteOptions::default();
+    write_opts.set_sync(true);
+    write_opts.disable_wal(false);
+    let cloned = write_opts.clone();
+    assert_eq!(format!("{:?}", cloned), format!("{:?}", write_opts));
+    assert_eq!(
+        format!("{:?}", cloned),
+        "WriteOptions { sync: true, disable_wal: false }"
+    );
+
+    let mut read_opts = ReadOptions::default();
+    read_opts.set_iterate_upper_bound(b"k2");
+    read_opts.set_total_order_seek(true);
+    assert_eq!(
+        format!("{:?}", read_opts.clone()),
+        "ReadOptions { fill_cache: true, snapshot: false, iterate_upper_bound: Some([107, 50]), \
+         total_order_seek: true }"
+    );
+
+    // The cloned options read from the same snapshot.
+    let temp_dir = TempDir::new("_rust_rocksdb_test_clone_read_options").unwrap();
+    let db = DB::open_default(temp_dir.path()).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    let snapshot = db.snapshot();
+    db.put(b"k1", b"v2").unwrap();
+
+    let mut read_opts = ReadOptions::default();
+    read_opts.set_snapshot(&snapshot);
+    let cloned = read_opts.clone();
+    drop(read_opts);
+    assert_eq!(&*db.get_opt(b"k1", &cloned).unwrap().unwrap(), b"v1");
+}
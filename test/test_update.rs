@@ -0,0 +1,118 @@
+// Copyright 2014 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use exonum_rocksdb::{Options, DB};
+use std::sync::Arc;
+use std::thread;
+use tempdir::TempDir;
+
+fn increment(old: Option<&[u8]>) -> Option<Vec<u8>> {
+    let n = old.map_or(0, |v| v[0]);
+    Some(vec![n + 1])
+}
+
+#[test]
+pub fn test_update() {
+    let temp_dir = TempDir::new("_rust_rocksdb_update").unwrap();
+    let db = DB::open_default(temp_dir.path()).unwrap();
+
+    // creates a missing key
+    assert_eq!(db.update(b"k1", increment).unwrap(), Some(vec![1]));
+    assert_eq!(&*db.get(b"k1").unwrap().unwrap(), &[1]);
+
+    // modifies an existing key
+    assert_eq!(db.update(b"k1", increment).unwrap(), Some(vec![2]));
+    assert_eq!(&*db.get(b"k1").unwrap().unwrap(), &[2]);
+
+    // deletes the key when the closure returns `None`
+    assert_eq!(db.update(b"k1", |_| None).unwrap(), None);
+    assert!(db.get(b"k1").unwrap().is_none());
+}
+
+#[test]
+pub fn test_update_cf() {
+    let temp_dir = TempDir::new("_rust_rocksdb_update_cf").unwrap();
+    let path = temp_dir.path();
+    {
+        let db = DB::open_default(path).unwrap();
+        db.create_cf("cf1", &Options::default()).unwrap();
+    }
+    let db = DB::open_cf(&Options::default(), path, &["cf1"]).unwrap();
+    let cf1 = db.cf_handle("cf1").unwrap();
+
+    db.update_cf(cf1, b"k1", increment).unwrap();
+    assert_eq!(&*db.get_cf(cf1, b"k1").unwrap().unwrap(), &[1]);
+    assert!(db.get(b"k1").unwrap().is_none());
+}
+
+#[test]
+pub fn test_update_concurrent() {
+    const THREADS: usize = 4;
+    const UPDATES: usize = 50;
+
+    let temp_dir = TempDir::new("_rust_rocksdb_update_concurrent").unwrap();
+    let db = Arc::new(DB::open_default(temp_dir.path()).unwrap());
+
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let db = Arc::clone(&db);
+            thread::spawn(move || {
+                for _ in 0..UPDATES {
+                    db.update(b"counter", increment).unwrap();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let value = db.get(b"counter").unwrap().unwrap();
+    assert_eq!(value[0] as usize, THREADS * UPDATES);
+}
+
+#[test]
+pub fn test_update_concurrent_default_cf_handle() {
+    const UPDATES: usize = 50;
+
+    let temp_dir = TempDir::new("_rust_rocksdb_update_concurrent_default_cf").unwrap();
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    let db = Arc::new(DB::open_cf(&opts, temp_dir.path(), &["default"]).unwrap());
+
+    // Updates through the default column family handle and without one
+    // are serialized against each other.
+    let handles: Vec<_> = (0..2)
+        .map(|i| {
+            let db = Arc::clone(&db);
+            thread::spawn(move || {
+                let default = db.cf_handle("default").unwrap();
+                for _ in 0..UPDATES {
+                    if i == 0 {
+                        db.update(b"counter", increment).unwrap();
+                    } else {
+                        db.update_cf(default, b"counter", increment).unwrap();
+                    }
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let value = db.get(b"counter").unwrap().unwrap();
+    assert_eq!(value[0] as usize, 2 * UPDATES);
+}
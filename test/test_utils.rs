@@ -0,0 +1,160 @@
+// Copyright 2014 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use exonum_rocksdb::utils::{
+    database_exists, get_cf_descriptors, health_check, health_check_cf_descriptors,
+};
+use exonum_rocksdb::{BlockBasedOptions, ColumnFamilyDescriptor, Options, DB};
+use std::cmp::Ordering;
+use std::fs;
+use tempdir::TempDir;
+
+#[test]
+pub fn test_database_exists() {
+    let temp_dir = TempDir::new("_rust_rocksdb_database_exists").unwrap();
+    let path = temp_dir.path().join("db");
+    assert!(!database_exists(&path));
+
+    fs::create_dir_all(&path).unwrap();
+    assert!(!database_exists(&path));
+
+    drop(DB::open_default(&path).unwrap());
+    assert!(database_exists(&path));
+}
+
+#[test]
+pub fn test_health_check() {
+    let temp_dir = TempDir::new("_rust_rocksdb_health_check").unwrap();
+    let path = temp_dir.path();
+    assert!(health_check(&Options::default(), path).is_err());
+
+    let db = DB::open_default(path).unwrap();
+    let cf1 = db.create_cf("cf1", &Options::default()).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    db.put_cf(cf1, b"k2", b"v2").unwrap();
+    db.put_cf(cf1, b"k3", b"v3").unwrap();
+
+    // The check works while the database is open for writing.
+    let report = health_check(&Options::default(), path).unwrap();
+    assert_eq!(
+        report.column_families,
+        vec!["default".to_owned(), "cf1".to_owned()]
+    );
+    assert_eq!(report.estimated_num_keys, 3);
+}
+
+#[test]
+pub fn test_health_check_without_options_file() {
+    let temp_dir = TempDir::new("_rust_rocksdb_health_check_without_options_file").unwrap();
+    let path = temp_dir.path();
+    {
+        let db = DB::open_default(path).unwrap();
+        db.create_cf("cf1", &Options::default()).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+    }
+    for entry in fs::read_dir(path).unwrap() {
+        let entry = entry.unwrap();
+        if entry.file_name().to_string_lossy().starts_with("OPTIONS-") {
+            fs::remove_file(entry.path()).unwrap();
+        }
+    }
+
+    let report = health_check(&Options::default(), path).unwrap();
+    assert_eq!(
+        report.column_families,
+        vec!["default".to_owned(), "cf1".to_owned()]
+    );
+    assert_eq!(report.estimated_num_keys, 1);
+}
+
+#[test]
+pub fn test_health_check_cf_descriptors() {
+    fn reverse(a: &[u8], b: &[u8]) -> Ordering {
+        b.cmp(a)
+    }
+    fn reverse_opts() -> Options {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.set_comparator("reverse", reverse);
+        opts
+    }
+
+    let temp_dir = TempDir::new("_rust_rocksdb_health_check_cf_descriptors").unwrap();
+    let path = temp_dir.path();
+    let db = DB::open(&reverse_opts(), path).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    db.put(b"k2", b"v2").unwrap();
+
+    // The comparator is not restored from the options file.
+    assert!(health_check(&Options::default(), path).is_err());
+
+    let cfs = vec![ColumnFamilyDescriptor::new("default", reverse_opts())];
+    let report = health_check_cf_descriptors(&reverse_opts(), path, cfs).unwrap();
+    assert_eq!(report.column_families, vec!["default".to_owned()]);
+    assert_eq!(report.estimated_num_keys, 2);
+}
+
+#[test]
+pub fn test_health_check_cf_descriptors_empty() {
+    let temp_dir = TempDir::new("_rust_rocksdb_health_check_cf_descriptors_empty").unwrap();
+    let path = temp_dir.path();
+    {
+        let db = DB::open_default(path).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+    }
+
+    // The default column family is checked even if not listed.
+    let report = health_check_cf_descriptors(&Options::default(), path, vec![]).unwrap();
+    assert_eq!(report.column_families, vec!["default".to_owned()]);
+    assert_eq!(report.estimated_num_keys, 1);
+}
+
+#[test]
+pub fn test_get_cf_descriptors() {
+    let temp_dir = TempDir::new("_rust_rocksdb_get_cf_descriptors").unwrap();
+    let path = temp_dir.path();
+    assert!(get_cf_descriptors(path).is_err());
+
+    {
+        let db = DB::open_default(path).unwrap();
+        let mut cf_opts = Options::default();
+        cf_opts.set_max_write_buffer_number(7);
+        let mut block_opts = BlockBasedOptions::default();
+        block_opts.set_block_size(12345);
+        cf_opts.set_block_based_table_factory(&block_opts);
+        let cf1 = db.create_cf("cf1", &cf_opts).unwrap();
+        db.put_cf(cf1, b"k1", b"v1").unwrap();
+    }
+
+    // Reopen twice, so that all retained options files are written with the restored options.
+    for _ in 0..2 {
+        let descriptors = get_cf_descriptors(path).unwrap();
+        let names: Vec<&str> = descriptors.iter().map(|cf| cf.name.as_str()).collect();
+        assert_eq!(names, vec!["default", "cf1"]);
+
+        let db = DB::open_cf_descriptors(&Options::default(), path, descriptors).unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+        assert_eq!(&*db.get_cf(cf1, b"k1").unwrap().unwrap(), b"v1");
+    }
+
+    for entry in fs::read_dir(path).unwrap() {
+        let entry = entry.unwrap();
+        if entry.file_name().to_string_lossy().starts_with("OPTIONS-") {
+            let contents = fs::read_to_string(entry.path()).unwrap();
+            assert!(contents.contains("max_write_buffer_number=7"));
+            assert!(contents.contains("block_size=12345"));
+        }
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright 2014 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use exonum_rocksdb::watch::{ChangeEvent, ChangeOp, WatchError};
+use exonum_rocksdb::{MergeOperands, Options, WriteBatch, WriteOptions, DB};
+use tempdir::TempDir;
+
+use std::time::Duration;
+
+fn concat_merge(_: &[u8], existing: Option<&[u8]>, operands: &mut MergeOperands) -> Vec<u8> {
+    let mut result = existing.map_or_else(Vec::new, |v| v.to_vec());
+    for op in operands {
+        result.extend_from_slice(op);
+    }
+    result
+}
+
+fn changes(events: &[ChangeEvent]) -> Vec<(&[u8], ChangeOp, Option<&[u8]>)> {
+    events
+        .iter()
+        .map(|e| {
+            (
+                e.key.as_slice(),
+                e.op,
+                e.value.as_ref().map(|v| v.as_slice()),
+            )
+        })
+        .collect()
+}
+
+#[test]
+pub fn test_watch_prefix() {
+    let temp_dir = TempDir::new("_rust_rocksdb_watch_prefix").unwrap();
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_wal_ttl_seconds(3600);
+    opts.set_merge_operator("concat", concat_merge);
+    let db = DB::open(&opts, temp_dir.path()).unwrap();
+    let cf1 = db.create_cf("cf1", &Options::default()).unwrap();
+
+    // Changes made before subscribing are not reported.
+    db.put(b"a:0", b"old").unwrap();
+    let mut watcher = db.watch_prefix(b"a:");
+    assert!(watcher.poll().unwrap().is_empty());
+
+    db.put(b"a:1", b"v1").unwrap();
+    db.put(b"b:1", b"v1").unwrap();
+    db.merge(b"a:1", b"v2").unwrap();
+
+    let mut batch = WriteBatch::default();
+    batch.put_cf(cf1, b"a:2", b"other cf").unwrap();
+    batch.put(b"a:2", b"v3").unwrap();
+    batch.delete(b"a:1").unwrap();
+    db.write(batch).unwrap();
+
+    let events = watcher.poll().unwrap();
+    assert_eq!(
+        changes(&events),
+        vec![
+            (&b"a:1"[..], ChangeOp::Put, Some(&b"v1"[..])),
+            (&b"a:1"[..], ChangeOp::Merge, Some(&b"v2"[..])),
+            (&b"a:2"[..], ChangeOp::Put, Some(&b"v3"[..])),
+            (&b"a:1"[..], ChangeOp::Delete, None),
+        ]
+    );
+    assert!(events.windows(2).all(|w| w[0].sequence < w[1].sequence));
+
+    // Only new changes are reported by the next poll.
+    assert!(watcher.poll().unwrap().is_empty());
+    db.put(b"a:3", b"v4").unwrap();
+    let events = watcher.poll().unwrap();
+    assert_eq!(
+        changes(&events),
+        vec![(&b"a:3"[..], ChangeOp::Put, Some(&b"v4"[..]))]
+    );
+}
+
+#[test]
+pub fn test_watch_prefix_recv() {
+    let temp_dir = TempDir::new("_rust_rocksdb_watch_prefix_recv").unwrap();
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_wal_ttl_seconds(3600);
+    let db = DB::open(&opts, temp_dir.path()).unwrap();
+
+    let mut watcher = db.watch_prefix(b"a:");
+    assert_eq!(
+        watcher.recv_timeout(Duration::from_millis(20)).unwrap(),
+        None
+    );
+
+    db.put(b"b:1", b"v1").unwrap();
+    db.put(b"a:1", b"v1").unwrap();
+    db.put(b"a:2", b"v2").unwrap();
+    assert_eq!(watcher.recv().unwrap().key, b"a:1");
+    let event = watcher.next().unwrap().unwrap();
+    assert_eq!(event.key, b"a:2");
+    assert_eq!(event.op, ChangeOp::Put);
+    assert_eq!(watcher.next_sequence(), event.sequence + 1);
+}
+
+#[test]
+pub fn test_watch_prefix_gap() {
+    let temp_dir = TempDir::new("_rust_rocksdb_watch_prefix_gap").unwrap();
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_wal_ttl_seconds(3600);
+    let db = DB::open(&opts, temp_dir.path()).unwrap();
+
+    let mut watcher = db.watch_prefix(b"a:");
+    let mut no_wal = WriteOptions::default();
+    no_wal.disable_wal(true);
+    db.put(b"a:1", b"v1").unwrap();
+    db.put_opt(b"a:2", b"v2", &no_wal).unwrap();
+    db.put(b"a:3", b"v3").unwrap();
+
+    // The changes before the gap are returned first.
+    let events = watcher.poll().unwrap();
+    assert_eq!(
+        changes(&events),
+        vec![(&b"a:1"[..], ChangeOp::Put, Some(&b"v1"[..]))]
+    );
+
+    // The gap is reported until the watcher is resumed after it.
+    let resume_from = match watcher.poll() {
+        Err(WatchError::Gap {
+            next_sequence,
+            resume_from,
+        }) => {
+            assert_eq!(next_sequence, events[0].sequence + 1);
+            resume_from
+        }
+        other => panic!("Unexpected result: {:?}", other),
+    };
+    assert!(watcher.poll().is_err());
+    watcher.resume_from(resume_from);
+    let events = watcher.poll().unwrap();
+    assert_eq!(
+        changes(&events),
+        vec![(&b"a:3"[..], ChangeOp::Put, Some(&b"v3"[..]))]
+    );
+}